@@ -0,0 +1,77 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+/// One row of a direction rating on abit-poisk or of another applicant source.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Application {
+    /// "Прізвище І. Б." as in `parsed_names`.
+    pub name: String,
+    /// Empty when a source doesn't tell the direction.
+    #[serde(default)]
+    pub direction: String,
+    pub priority: Option<i16>,
+    pub score: Option<f64>,
+    pub status: Option<String>,
+    /// Original documents were submitted for this direction.
+    #[serde(default)]
+    pub original: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    Text,
+    JsonLines,
+    Csv,
+}
+
+impl FromStr for Format {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(Format::Text),
+            "jsonl" => Ok(Format::JsonLines),
+            "csv" => Ok(Format::Csv),
+            _ => Err("must be text, jsonl or csv"),
+        }
+    }
+}
+
+impl Format {
+    pub fn extension(self) -> &'static str {
+        match self {
+            Format::Text => "",
+            Format::JsonLines => ".jsonl",
+            Format::Csv => ".csv",
+        }
+    }
+
+    /// Text keeps the old output of one name per line.
+    pub fn write(self, applications: &[Application]) -> Result<Vec<u8>> {
+        let mut output = Vec::new();
+        match self {
+            Format::Text => {
+                for application in applications {
+                    output.extend_from_slice(application.name.as_bytes());
+                    output.push(b'\n');
+                }
+            }
+            Format::JsonLines => {
+                for application in applications {
+                    serde_json::to_writer(&mut output, application)?;
+                    output.push(b'\n');
+                }
+            }
+            Format::Csv => {
+                let mut writer = csv::Writer::from_writer(&mut output);
+                for application in applications {
+                    writer.serialize(application)?;
+                }
+                writer.flush()?;
+            }
+        }
+        Ok(output)
+    }
+}
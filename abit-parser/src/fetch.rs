@@ -0,0 +1,111 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use std::path::PathBuf;
+
+/// Source of abit-poisk pages, the site itself or pages saved earlier.
+#[async_trait]
+pub trait Fetcher: Send + Sync {
+    async fn fetch(&self, url: &str) -> Result<String>;
+
+    /// Whether a failed fetch may succeed when repeated.
+    fn retryable(&self) -> bool {
+        true
+    }
+}
+
+pub struct HttpFetcher {
+    client: reqwest::Client,
+}
+
+impl HttpFetcher {
+    pub fn new() -> Self {
+        HttpFetcher {
+            client: reqwest::Client::new(),
+        }
+    }
+}
+
+impl Default for HttpFetcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl Fetcher for HttpFetcher {
+    async fn fetch(&self, url: &str) -> Result<String> {
+        Ok(self
+            .client
+            .get(url)
+            .send()
+            .await?
+            .error_for_status()?
+            .text()
+            .await?)
+    }
+}
+
+/// File a page is saved to: `123456.html` for the first page of a direction and
+/// `123456_page_2.html` for the next ones.
+pub fn fixture_name(url: &str) -> String {
+    let path = url.rsplit("/direction/").next().unwrap_or(url);
+    let name = path
+        .replace("/?page=", "_page_")
+        .replace(['/', '?', '='], "_");
+    format!("{}.html", name.trim_end_matches('_'))
+}
+
+/// Reads pages from a directory instead of the site, see `fixture_name`.
+pub struct FixtureFetcher {
+    dir: PathBuf,
+}
+
+impl FixtureFetcher {
+    pub fn new<P: Into<PathBuf>>(dir: P) -> Self {
+        FixtureFetcher { dir: dir.into() }
+    }
+}
+
+#[async_trait]
+impl Fetcher for FixtureFetcher {
+    async fn fetch(&self, url: &str) -> Result<String> {
+        let path = self.dir.join(fixture_name(url));
+        tokio::fs::read_to_string(&path)
+            .await
+            .with_context(|| format!("Failed to read {}", path.display()))
+    }
+
+    /// A missing file won't appear on the next attempt.
+    fn retryable(&self) -> bool {
+        false
+    }
+}
+
+/// Saves every fetched page into a directory, so that it can be replayed with `FixtureFetcher`.
+pub struct RecordingFetcher<F> {
+    inner: F,
+    dir: PathBuf,
+}
+
+impl<F: Fetcher> RecordingFetcher<F> {
+    pub fn new<P: Into<PathBuf>>(inner: F, dir: P) -> Self {
+        RecordingFetcher {
+            inner,
+            dir: dir.into(),
+        }
+    }
+}
+
+#[async_trait]
+impl<F: Fetcher> Fetcher for RecordingFetcher<F> {
+    async fn fetch(&self, url: &str) -> Result<String> {
+        let page = self.inner.fetch(url).await?;
+        tokio::fs::create_dir_all(&self.dir).await?;
+        tokio::fs::write(self.dir.join(fixture_name(url)), &page).await?;
+        Ok(page)
+    }
+
+    fn retryable(&self) -> bool {
+        self.inner.retryable()
+    }
+}
@@ -0,0 +1,76 @@
+use anyhow::Result;
+use sqlx::PgPool;
+use std::collections::BTreeSet;
+
+use crate::application::Application;
+
+/// Names added to and removed from `parsed_names` by an import.
+#[derive(Debug, Default)]
+pub struct Diff {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+/// Replaces `applications` and the names in `parsed_names` in one transaction,
+/// a dry run only computes the diff.
+pub async fn import(pool: &PgPool, applications: &[Application], dry_run: bool) -> Result<Diff> {
+    let names: BTreeSet<String> = applications
+        .iter()
+        .map(|application| application.name.clone())
+        .collect();
+    let mut transaction = pool.begin().await?;
+    sqlx::query("LOCK TABLE parsed_names, applications IN EXCLUSIVE MODE")
+        .execute(&mut transaction)
+        .await?;
+    let current: BTreeSet<String> = sqlx::query_scalar("SELECT name FROM parsed_names")
+        .fetch_all(&mut transaction)
+        .await?
+        .into_iter()
+        .collect();
+    let diff = Diff {
+        added: names.difference(&current).cloned().collect(),
+        removed: current.difference(&names).cloned().collect(),
+    };
+    if dry_run {
+        transaction.rollback().await?;
+        return Ok(diff);
+    }
+
+    sqlx::query("DELETE FROM parsed_names WHERE name = ANY($1)")
+        .bind(&diff.removed)
+        .execute(&mut transaction)
+        .await?;
+    sqlx::query(
+        "INSERT INTO parsed_names (name) SELECT unnest($1::VARCHAR[]) ON CONFLICT (name) DO NOTHING",
+    )
+    .bind(&diff.added)
+    .execute(&mut transaction)
+    .await?;
+
+    sqlx::query("DELETE FROM applications")
+        .execute(&mut transaction)
+        .await?;
+    sqlx::query(
+        "INSERT INTO applications (name, direction, priority, score, status, original)
+            SELECT * FROM unnest($1::VARCHAR[], $2::VARCHAR[], $3::SMALLINT[],
+                $4::DOUBLE PRECISION[], $5::VARCHAR[], $6::BOOLEAN[])",
+    )
+    .bind(column(applications, |application| application.name.clone()))
+    .bind(column(applications, |application| {
+        application.direction.clone()
+    }))
+    .bind(column(applications, |application| application.priority))
+    .bind(column(applications, |application| application.score))
+    .bind(column(applications, |application| {
+        application.status.clone()
+    }))
+    .bind(column(applications, |application| application.original))
+    .execute(&mut transaction)
+    .await?;
+    transaction.commit().await?;
+    Ok(diff)
+}
+
+fn column<T, F: Fn(&Application) -> T>(applications: &[Application], field: F) -> Vec<T> {
+    applications.iter().map(field).collect()
+}
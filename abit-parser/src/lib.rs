@@ -0,0 +1,9 @@
+pub mod application;
+pub mod fetch;
+pub mod import;
+pub mod parser;
+pub mod source;
+
+pub use application::{Application, Format};
+pub use parser::Parser;
+pub use source::ApplicantSource;
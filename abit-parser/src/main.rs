@@ -1,11 +1,15 @@
+use abit_parser::fetch::{FixtureFetcher, HttpFetcher, RecordingFetcher};
+use abit_parser::parser::{FailedPage, Options};
+use abit_parser::source::{self, AbitPoisk};
+use abit_parser::{import, ApplicantSource, Application, Format, Parser};
 use anyhow::Result;
 use regex::Regex;
+use sqlx::PgPool;
+use std::path::PathBuf;
 use std::process;
+use std::time::Duration;
 use tokio::fs::File;
-
-use crate::parser::Parser;
-
-mod parser;
+use tokio::io::AsyncWriteExt;
 
 const HELP: &str = "\
 apit-parser
@@ -16,6 +20,19 @@ FLAGS:
 OPTIONS:
   --year NUMBER         Sets a number of year
   --single-file STATE   Sets a state to use single-file
+  --format FORMAT       Output as text (names only), jsonl or csv
+  --database-url URL    Replaces applications and parsed_names in this database
+                        instead of writing files
+  --dry-run             With --database-url, only prints names to add and remove
+  --from-dir DIR        Parses pages saved in DIR instead of the site
+  --save-dir DIR        Saves fetched pages into DIR for --from-dir
+  --concurrency NUMBER  Pages fetched at the same time [default: 3]
+  --retries NUMBER      Attempts after a failed request [default: 3]
+  --rate-limit MS       Least time between two requests
+  --base-url URL        Reads ratings from URL followed by the code instead of
+                        abit-poisk
+  --source FILE         Adds applicants from a CSV, XLSX or JSON file, can be
+                        repeated. The sources are merged into a single list
 ARGS:
   <INPUT>
 ";
@@ -23,6 +40,14 @@ ARGS:
 #[derive(Debug)]
 struct AppArgs {
     single_file: bool,
+    format: Format,
+    database_url: Option<String>,
+    dry_run: bool,
+    from_dir: Option<PathBuf>,
+    save_dir: Option<PathBuf>,
+    options: Options,
+    base_url: Option<String>,
+    sources: Vec<PathBuf>,
     year: u8,
     input: Vec<String>,
 }
@@ -37,30 +62,128 @@ async fn main() {
         }
     };
 
-    let parser = Parser::new(args.year);
+    let parser = match (&args.from_dir, &args.save_dir) {
+        (Some(dir), _) => Parser::with_fetcher(args.year, FixtureFetcher::new(dir)),
+        (None, Some(dir)) => {
+            Parser::with_fetcher(args.year, RecordingFetcher::new(HttpFetcher::new(), dir))
+        }
+        (None, None) => Parser::new(args.year),
+    }
+    .with_options(args.options.clone());
+    let parser = match &args.base_url {
+        Some(url) => parser.with_url(url.as_str()),
+        None => parser,
+    };
+    if args.database_url.is_some() || !args.sources.is_empty() {
+        if let Err(error) = merge_sources(parser, &args).await {
+            eprintln!("Error: {:#}.", error);
+            process::exit(1);
+        }
+    } else {
+        let failed = write_files(&parser, &args).await;
+        if !failed.is_empty() {
+            eprintln!("Failed pages:");
+            for page in &failed {
+                eprintln!("  {}: {}", page.url, page.error);
+                if page.last_known {
+                    eprintln!("    pages after it were not reached");
+                }
+            }
+            process::exit(1);
+        }
+    }
+}
+
+/// Writes what could be fetched and returns the pages that failed.
+async fn write_files(parser: &Parser, args: &AppArgs) -> Vec<FailedPage> {
+    let mut failed = Vec::new();
     if args.single_file {
-        let mut file = File::create("all").await.expect("Failed to create file");
-        for code in args.input {
-            match parser.get_info(&code, &mut file).await {
-                Err(error) => {
-                    eprintln!("Error: {}.", error);
+        // Collected first so that a CSV file gets a single header.
+        let mut applications = Vec::new();
+        for code in &args.input {
+            match parser.get_report(code).await {
+                Ok(report) => {
+                    applications.extend(report.applications);
+                    failed.extend(report.failed);
                 }
-                _ => {}
+                Err(error) => failed.push(first_page_failed(code, error)),
             }
         }
+        let mut file = File::create(format!("all{}", args.format.extension()))
+            .await
+            .expect("Failed to create file");
+        let written = match args.format.write(&applications) {
+            Ok(output) => file.write_all(&output).await.map_err(anyhow::Error::from),
+            Err(error) => Err(error),
+        };
+        if let Err(error) = written {
+            eprintln!("Error: {}.", error);
+        }
     } else {
-        for code in args.input {
-            let mut file = File::create(&code)
+        for code in &args.input {
+            let mut file = File::create(format!("{}{}", code, args.format.extension()))
                 .await
-                .expect(&format!("Failed to create file with code: {}", code));
-            match parser.get_info(&code, &mut file).await {
-                Err(error) => {
-                    eprintln!("Error: {}.", error);
-                }
-                _ => {}
+                .unwrap_or_else(|_| panic!("Failed to create file with code: {}", code));
+            match parser.get_info(code, args.format, &mut file).await {
+                Ok(report) => failed.extend(report.failed),
+                Err(error) => failed.push(first_page_failed(code, error)),
             }
         }
     }
+    failed
+}
+
+/// Nothing of the direction was parsed, it is reported by its code.
+fn first_page_failed(code: &str, error: anyhow::Error) -> FailedPage {
+    FailedPage {
+        url: code.to_string(),
+        error: error.to_string(),
+        last_known: true,
+    }
+}
+
+/// Any failed source aborts, a partial list would drop valid names.
+async fn merge_sources(parser: Parser, args: &AppArgs) -> Result<()> {
+    let mut sources: Vec<Box<dyn ApplicantSource>> = Vec::new();
+    if !args.input.is_empty() {
+        sources.push(Box::new(AbitPoisk::new(parser, args.input.clone())));
+    }
+    sources.extend(args.sources.iter().map(source::file_source));
+    let applications = source::merge(&sources).await?;
+    match &args.database_url {
+        Some(database_url) => import_names(database_url, &applications, args.dry_run).await,
+        None => {
+            let mut file = File::create(format!("all{}", args.format.extension())).await?;
+            file.write_all(&args.format.write(&applications)?).await?;
+            Ok(())
+        }
+    }
+}
+
+async fn import_names(
+    database_url: &str,
+    applications: &[Application],
+    dry_run: bool,
+) -> Result<()> {
+    if applications.is_empty() {
+        anyhow::bail!("no names parsed, refusing to clear parsed_names");
+    }
+    let pool = PgPool::connect(database_url).await?;
+    let diff = import::import(&pool, applications, dry_run).await?;
+    for name in &diff.added {
+        println!("+ {}", name);
+    }
+    for name in &diff.removed {
+        println!("- {}", name);
+    }
+    println!(
+        "{} added, {} removed, {} applications{}",
+        diff.added.len(),
+        diff.removed.len(),
+        applications.len(),
+        if dry_run { " (dry run)" } else { "" }
+    );
+    Ok(())
 }
 
 fn parse_args() -> Result<AppArgs, pico_args::Error> {
@@ -75,21 +198,40 @@ fn parse_args() -> Result<AppArgs, pico_args::Error> {
 
     let args = AppArgs {
         single_file: pargs.opt_value_from_str("--single-file")?.unwrap_or(true),
+        format: pargs
+            .opt_value_from_str("--format")?
+            .unwrap_or(Format::Text),
+        database_url: pargs.opt_value_from_str("--database-url")?,
+        dry_run: pargs.contains("--dry-run"),
+        from_dir: pargs.opt_value_from_str("--from-dir")?,
+        save_dir: pargs.opt_value_from_str("--save-dir")?,
+        options: {
+            let default = Options::default();
+            Options {
+                concurrency: pargs
+                    .opt_value_from_str("--concurrency")?
+                    .unwrap_or(default.concurrency),
+                retries: pargs
+                    .opt_value_from_str("--retries")?
+                    .unwrap_or(default.retries),
+                rate_limit: pargs
+                    .opt_value_from_str("--rate-limit")?
+                    .map(Duration::from_millis),
+                ..default
+            }
+        },
+        base_url: pargs.opt_value_from_str("--base-url")?,
+        sources: pargs.values_from_str("--source")?,
         year: pargs.opt_value_from_fn("--year", parse_year)?.unwrap_or(21),
         input: {
             pargs
                 .finish()
                 .to_vec()
                 .iter()
-                .filter_map(|code| match code.to_str().map(String::from) {
-                    Some(code) => {
-                        if regex.is_match(&code) {
-                            Some(code)
-                        } else {
-                            None
-                        }
-                    }
-                    None => None,
+                .filter_map(|code| {
+                    code.to_str()
+                        .map(String::from)
+                        .filter(|code| regex.is_match(code))
                 })
                 .collect()
         },
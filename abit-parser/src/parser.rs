@@ -1,85 +1,298 @@
-use anyhow::{Context, Error, Result};
-use futures::{stream, StreamExt, TryStreamExt};
-use scraper::{Html, Selector};
+use anyhow::{bail, Result};
+use futures::{stream, StreamExt};
+use regex::Regex;
+use scraper::{ElementRef, Html, Selector};
+use std::collections::BTreeSet;
+use std::time::Duration;
 use tokio::fs::File;
 use tokio::io::AsyncWriteExt;
+use tokio::sync::Mutex;
+use tokio::time::Instant;
+
+use crate::application::{Application, Format};
+use crate::fetch::{Fetcher, HttpFetcher};
+
+/// How pages are requested.
+#[derive(Debug, Clone)]
+pub struct Options {
+    /// Pages fetched at the same time.
+    pub concurrency: usize,
+    /// Attempts after the first failed one, waiting `backoff`, then twice as long and so on.
+    pub retries: u32,
+    pub backoff: Duration,
+    /// Least time between two requests.
+    pub rate_limit: Option<Duration>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            concurrency: 3,
+            retries: 3,
+            backoff: Duration::from_millis(500),
+            rate_limit: None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct FailedPage {
+    pub url: String,
+    pub error: String,
+    /// No fetched page links past this one, so pages after it, if any, were not reached.
+    pub last_known: bool,
+}
+
+/// Rows of a direction and the pages that could not be fetched even after retries.
+#[derive(Debug, Default)]
+pub struct Report {
+    pub applications: Vec<Application>,
+    pub failed: Vec<FailedPage>,
+}
 
 pub struct Parser {
     url: String,
+    fetcher: Box<dyn Fetcher>,
+    options: Options,
+    next_request: Mutex<Instant>,
 }
 
 impl Parser {
     pub fn new(year: u8) -> Self {
+        Self::with_fetcher(year, HttpFetcher::new())
+    }
+
+    pub fn with_fetcher<F: Fetcher + 'static>(year: u8, fetcher: F) -> Self {
         let url = format!("https://abit-poisk.org.ua/rate20{}/direction/", year);
-        Parser { url }
+        Parser {
+            url,
+            fetcher: Box::new(fetcher),
+            options: Options::default(),
+            next_request: Mutex::new(Instant::now()),
+        }
+    }
+
+    pub fn with_options(mut self, options: Options) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Ratings are read from `{url}{code}` instead of the abit-poisk page of the year.
+    pub fn with_url<S: Into<String>>(mut self, url: S) -> Self {
+        self.url = url.into();
+        self
     }
-    pub async fn get_info(&self, code: &str, file: &mut File) -> Result<()> {
-        let url = &format!("{}{}", &self.url, code);
-        let document = Self::parse_html(url).await?;
-        let pages = Self::parse_pages(&document).await?;
-
-        if pages == 1 {
-            let names = Self::parse_names(document).await;
-            file.write_all((names.join("\n") + "\n").as_bytes()).await?;
-            return Ok(());
+
+    pub async fn get_info(&self, code: &str, format: Format, file: &mut File) -> Result<Report> {
+        let report = self.get_report(code).await?;
+        file.write_all(&format.write(&report.applications)?).await?;
+        Ok(report)
+    }
+
+    /// Rows from every page of the direction with this code, fails if any page is missing.
+    pub async fn get_applications(&self, code: &str) -> Result<Vec<Application>> {
+        let report = self.get_report(code).await?;
+        if let Some(page) = report.failed.first() {
+            bail!(
+                "{} pages of {} failed{}, first {}: {}",
+                report.failed.len(),
+                code,
+                if report.failed.iter().any(|page| page.last_known) {
+                    " and later pages were not reached"
+                } else {
+                    ""
+                },
+                page.url,
+                page.error
+            );
         }
+        Ok(report.applications)
+    }
 
-        let stream = stream::iter(2..=pages)
-            .map(|page| async move { Self::parse_html(&format!("{}/?page={}", url, page)).await })
-            .buffer_unordered(3)
-            .chain(stream::once(async { Ok::<Html, Error>(document) }))
-            .map(|document| async {
-                match document {
-                    Ok(document) => Ok(Self::parse_names(document).await),
-                    Err(error) => Err(error),
-                }
-            })
-            .buffer_unordered(3);
+    /// Follows the pagination links of every fetched page until there are no new ones,
+    /// only the first page failing is an error.
+    pub async fn get_report(&self, code: &str) -> Result<Report> {
+        let url = format!("{}{}", &self.url, code);
+        let document = self.parse_html(&url).await?;
+        let mut seen = BTreeSet::from([1]);
+        let mut next = Self::parse_page_links(&document);
+        let mut report = Report {
+            applications: Self::parse_applications(&document, code),
+            failed: Vec::new(),
+        };
+        let mut failed_pages = Vec::new();
 
-        futures::pin_mut!(stream);
-        while let Some(names) = stream.try_next().await? {
-            file.write_all((names.join("\n") + "\n").as_bytes()).await?;
+        loop {
+            let pages = next.difference(&seen).copied().collect::<Vec<u32>>();
+            if pages.is_empty() {
+                let last_page = seen.iter().next_back().copied().unwrap_or(1);
+                for (failed, page) in report.failed.iter_mut().zip(failed_pages) {
+                    failed.last_known = page >= last_page;
+                }
+                return Ok(report);
+            }
+            seen.extend(&pages);
+            let results = stream::iter(pages)
+                .map(|page| {
+                    let url = format!("{}/?page={}", url, page);
+                    async move {
+                        let result = self.parse_html(&url).await.map(|document| {
+                            (
+                                Self::parse_page_links(&document),
+                                Self::parse_applications(&document, code),
+                            )
+                        });
+                        (page, url, result)
+                    }
+                })
+                .buffer_unordered(self.options.concurrency.max(1))
+                .collect::<Vec<_>>()
+                .await;
+            next = BTreeSet::new();
+            for (page, url, result) in results {
+                match result {
+                    Ok((links, applications)) => {
+                        next.extend(links);
+                        report.applications.extend(applications);
+                    }
+                    Err(error) => {
+                        failed_pages.push(page);
+                        report.failed.push(FailedPage {
+                            url,
+                            error: error.to_string(),
+                            last_known: false,
+                        });
+                    }
+                }
+            }
         }
-        Ok(())
     }
 
-    async fn parse_pages(document: &Html) -> Result<u8> {
-        let div_selector =
-            Selector::parse(r#"div[class=""]"#).expect("Failed to parse div selector");
-        let pages = match document.select(&div_selector).last() {
-            Some(div) => {
-                let selector = Selector::parse(
-                    r#"a[data-scroll-on-load=""].btn.btn-default.ajax.secondary-text"#,
-                )
+    /// Page numbers the pagination buttons lead to.
+    fn parse_page_links(document: &Html) -> BTreeSet<u32> {
+        let selector =
+            Selector::parse(r#"a[data-scroll-on-load=""].btn.btn-default.ajax.secondary-text"#)
                 .expect("Failed to parse page selector");
-                let mut elements = div.select(&selector);
-                match elements.by_ref().count() {
-                    0 => 1,
-                    x if x < 5 => (x - 1) as u8,
-                    x => elements
-                        .skip(x - 2)
-                        .next()
-                        .context("Failed to get pages number")?
-                        .inner_html()
-                        .parse()?,
+        let regex = Regex::new(r"[?&]page=([0-9]+)").expect("Failed to create regex");
+        document
+            .select(&selector)
+            .filter_map(|element| element.value().attr("href"))
+            .filter_map(|href| regex.captures(href)?.get(1)?.as_str().parse().ok())
+            .collect()
+    }
+
+    async fn parse_html(&self, url: &str) -> Result<Html> {
+        let mut attempt = 0;
+        loop {
+            self.wait_for_turn().await;
+            match self.fetcher.fetch(url).await {
+                Ok(response) => return Ok(Html::parse_document(&response)),
+                Err(error) if attempt >= self.options.retries || !self.fetcher.retryable() => {
+                    return Err(error)
+                }
+                Err(_) => {
+                    tokio::time::sleep(self.options.backoff * 2u32.saturating_pow(attempt)).await;
+                    attempt += 1;
                 }
             }
-            None => 1,
-        };
-        Ok(pages)
+        }
     }
 
-    async fn parse_html(url: &str) -> Result<Html> {
-        let response = reqwest::get(url).await?.text().await?;
-        Ok(Html::parse_document(&response))
+    /// Spaces requests by `rate_limit`.
+    async fn wait_for_turn(&self) {
+        if let Some(rate_limit) = self.options.rate_limit {
+            let turn = {
+                let mut next_request = self.next_request.lock().await;
+                let turn = (*next_request).max(Instant::now());
+                *next_request = turn + rate_limit;
+                turn
+            };
+            tokio::time::sleep_until(turn).await;
+        }
     }
 
-    async fn parse_names(document: Html) -> Vec<String> {
+    /// Columns are found by their headers, fields of a missing column stay empty.
+    fn parse_applications(document: &Html, code: &str) -> Vec<Application> {
         let selector =
             Selector::parse(r#"a[href^="/#search-"]"#).expect("Failed to parse name selector");
+        let cell_selector = Selector::parse("td").expect("Failed to parse cell selector");
+        let header_selector = Selector::parse("th").expect("Failed to parse header selector");
         document
             .select(&selector)
-            .map(|element| element.inner_html().trim().to_string())
+            .map(|element| {
+                let row = element
+                    .ancestors()
+                    .filter_map(ElementRef::wrap)
+                    .find(|ancestor| ancestor.value().name() == "tr");
+                let columns = row
+                    .and_then(|row| {
+                        row.ancestors()
+                            .filter_map(ElementRef::wrap)
+                            .find(|ancestor| ancestor.value().name() == "table")
+                    })
+                    .map(|table| {
+                        table
+                            .select(&header_selector)
+                            .map(|header| Column::from_header(&text(header)))
+                            .collect::<Vec<_>>()
+                    })
+                    .unwrap_or_default();
+                let cells = row
+                    .map(|row| row.select(&cell_selector).map(text).collect::<Vec<_>>())
+                    .unwrap_or_default();
+                let cell = |column: Column| {
+                    columns
+                        .iter()
+                        .position(|kind| *kind == column)
+                        .and_then(|index| cells.get(index))
+                        .filter(|cell| !cell.is_empty())
+                };
+                Application {
+                    name: element.inner_html().trim().to_string(),
+                    direction: code.to_string(),
+                    priority: cell(Column::Priority).and_then(|cell| cell.parse().ok()),
+                    score: cell(Column::Score).and_then(|cell| cell.replace(',', ".").parse().ok()),
+                    status: cell(Column::Status).cloned(),
+                    original: cell(Column::Original).is_some_and(|cell| {
+                        !matches!(cell.to_lowercase().as_str(), "-" | "—" | "ні" | "копія")
+                    }),
+                }
+            })
             .collect::<Vec<_>>()
     }
 }
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Column {
+    Priority,
+    Score,
+    Status,
+    Original,
+    Other,
+}
+
+impl Column {
+    fn from_header(header: &str) -> Self {
+        let header = header.to_lowercase();
+        if header == "п" || header.contains("пріоритет") {
+            Column::Priority
+        } else if header == "σ" || header.contains("бал") {
+            Column::Score
+        } else if header.contains("стан") || header.contains("статус") {
+            Column::Status
+        } else if header == "д" || header.contains("оригінал") {
+            Column::Original
+        } else {
+            Column::Other
+        }
+    }
+}
+
+fn text(element: ElementRef) -> String {
+    element
+        .text()
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
@@ -0,0 +1,347 @@
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use calamine::{open_workbook_auto, Reader};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use crate::application::Application;
+use crate::parser::Parser;
+
+/// Where the list of applicants comes from, see `merge` for combining several.
+#[async_trait(?Send)]
+pub trait ApplicantSource {
+    /// Shown in errors and the import summary.
+    fn name(&self) -> String;
+
+    async fn applications(&self) -> Result<Vec<Application>>;
+}
+
+/// The ratings of the given directions on abit-poisk.
+pub struct AbitPoisk {
+    parser: Parser,
+    codes: Vec<String>,
+}
+
+impl AbitPoisk {
+    pub fn new(parser: Parser, codes: Vec<String>) -> Self {
+        AbitPoisk { parser, codes }
+    }
+}
+
+#[async_trait(?Send)]
+impl ApplicantSource for AbitPoisk {
+    fn name(&self) -> String {
+        format!("abit-poisk {}", self.codes.join(", "))
+    }
+
+    async fn applications(&self) -> Result<Vec<Application>> {
+        let mut applications = Vec::new();
+        for code in &self.codes {
+            applications.extend(self.parser.get_applications(code).await?);
+        }
+        Ok(applications)
+    }
+}
+
+/// A CSV or Excel export, e.g. from EDEBO. Columns are found by their headers,
+/// the name is either one column or separate last name, name and patronymic.
+pub struct TableFile {
+    path: PathBuf,
+}
+
+impl TableFile {
+    pub fn new<P: Into<PathBuf>>(path: P) -> Self {
+        TableFile { path: path.into() }
+    }
+
+    fn read_rows(&self) -> Result<Vec<Vec<String>>> {
+        let extension = self
+            .path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .unwrap_or_default()
+            .to_lowercase();
+        if extension == "csv" {
+            let mut reader = csv::ReaderBuilder::new()
+                .has_headers(false)
+                .flexible(true)
+                .from_path(&self.path)?;
+            return reader
+                .records()
+                .map(|record| Ok(record?.iter().map(String::from).collect()))
+                .collect();
+        }
+        let mut workbook = open_workbook_auto(&self.path)?;
+        let range = workbook
+            .worksheet_range_at(0)
+            .context("The workbook has no sheets")??;
+        Ok(range
+            .rows()
+            .map(|row| row.iter().map(|cell| cell.to_string()).collect())
+            .collect())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Column {
+    FullName,
+    LastName,
+    FirstName,
+    Patronymic,
+    Direction,
+    Priority,
+    Score,
+    Status,
+    Original,
+    Other,
+}
+
+impl Column {
+    fn from_header(header: &str) -> Self {
+        let header = header.trim().to_lowercase();
+        let header = header.as_str();
+        if header == "піб" || header == "name" || header.starts_with("прізвище,") {
+            Column::FullName
+        } else if header == "прізвище" || header == "last name" {
+            Column::LastName
+        } else if header == "ім'я" || header == "імʼя" || header == "first name" {
+            Column::FirstName
+        } else if header == "по батькові" || header == "patronymic" {
+            Column::Patronymic
+        } else if header == "priority" || header.contains("пріоритет") {
+            Column::Priority
+        } else if header == "score" || header.contains("бал") {
+            Column::Score
+        } else if header == "direction"
+            || header.contains("спеціальн")
+            || header.contains("конкурсна пропозиція")
+            || header.contains("конкурсної пропозиції")
+        {
+            Column::Direction
+        } else if header == "status" || header.contains("статус") || header == "стан" {
+            Column::Status
+        } else if header == "original" || header.contains("оригінал") {
+            Column::Original
+        } else {
+            Column::Other
+        }
+    }
+}
+
+/// Exports name the offer like "123456 Бакалавр, денна, ...", only the code goes
+/// into `direction`. A cell without a code leaves the direction empty.
+fn parse_direction(cell: &str) -> String {
+    cell.split(|c: char| !c.is_ascii_digit())
+        .find(|part| part.len() >= 3)
+        .unwrap_or_default()
+        .to_string()
+}
+
+fn parse_flag(cell: &str) -> bool {
+    matches!(
+        cell.trim().to_lowercase().as_str(),
+        "+" | "1" | "true" | "так" | "оригінал"
+    )
+}
+
+/// Maps the rows under the header row to applications, rows without a name are skipped.
+fn from_table(rows: Vec<Vec<String>>) -> Result<Vec<Application>> {
+    let mut rows = rows.into_iter();
+    let columns = rows
+        .next()
+        .context("The table is empty")?
+        .iter()
+        .map(|header| Column::from_header(header))
+        .collect::<Vec<_>>();
+    let has = |column| columns.contains(&column);
+    if !has(Column::FullName) && !has(Column::LastName) {
+        bail!("The table has no name column");
+    }
+    Ok(rows
+        .filter_map(|row| {
+            let cell = |column: Column| {
+                columns
+                    .iter()
+                    .position(|kind| *kind == column)
+                    .and_then(|index| row.get(index))
+                    .map(|cell| cell.trim())
+                    .filter(|cell| !cell.is_empty())
+            };
+            let name = match cell(Column::FullName) {
+                Some(name) => name.to_string(),
+                None => [Column::LastName, Column::FirstName, Column::Patronymic]
+                    .iter()
+                    .filter_map(|column| cell(*column))
+                    .collect::<Vec<_>>()
+                    .join(" "),
+            };
+            if name.is_empty() {
+                return None;
+            }
+            Some(Application {
+                name,
+                direction: cell(Column::Direction)
+                    .map(parse_direction)
+                    .unwrap_or_default(),
+                priority: cell(Column::Priority).and_then(|cell| cell.parse().ok()),
+                score: cell(Column::Score).and_then(|cell| cell.replace(',', ".").parse().ok()),
+                status: cell(Column::Status).map(String::from),
+                original: cell(Column::Original).is_some_and(parse_flag),
+            })
+        })
+        .collect())
+}
+
+#[async_trait(?Send)]
+impl ApplicantSource for TableFile {
+    fn name(&self) -> String {
+        self.path.display().to_string()
+    }
+
+    async fn applications(&self) -> Result<Vec<Application>> {
+        from_table(self.read_rows()?)
+    }
+}
+
+/// A JSON array or JSON Lines of `Application` records, as `--format jsonl` writes them.
+pub struct JsonFile {
+    path: PathBuf,
+}
+
+impl JsonFile {
+    pub fn new<P: Into<PathBuf>>(path: P) -> Self {
+        JsonFile { path: path.into() }
+    }
+}
+
+#[async_trait(?Send)]
+impl ApplicantSource for JsonFile {
+    fn name(&self) -> String {
+        self.path.display().to_string()
+    }
+
+    async fn applications(&self) -> Result<Vec<Application>> {
+        let content = tokio::fs::read_to_string(&self.path).await?;
+        if content.trim_start().starts_with('[') {
+            return Ok(serde_json::from_str(&content)?);
+        }
+        content
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| Ok(serde_json::from_str(line)?))
+            .collect()
+    }
+}
+
+/// Picks the source for a file by its extension.
+pub fn file_source<P: Into<PathBuf>>(path: P) -> Box<dyn ApplicantSource> {
+    let path = path.into();
+    let extension = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .unwrap_or_default()
+        .to_lowercase();
+    match extension.as_str() {
+        "json" | "jsonl" => Box::new(JsonFile::new(path)),
+        _ => Box::new(TableFile::new(path)),
+    }
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first
+            .to_uppercase()
+            .chain(chars.flat_map(char::to_lowercase))
+            .collect(),
+        None => String::new(),
+    }
+}
+
+/// Brings a name to the "Прізвище І. Б." form of abit-poisk and `parsed_names`.
+pub fn normalize_name(name: &str) -> String {
+    // The database compares names with `normalize_apostrophes`, which does the same.
+    let name = name.replace(['’', 'ʼ', '`'], "'");
+    let words = name.split_whitespace().collect::<Vec<_>>();
+    let (last_name, rest) = match words.split_first() {
+        Some(split) => split,
+        None => return String::new(),
+    };
+    // Each part of a double surname may be written in capitals on its own.
+    let last_name = last_name
+        .split('-')
+        .map(|part| {
+            if part.chars().any(char::is_lowercase) {
+                part.to_string()
+            } else {
+                capitalize(part)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("-");
+    let initials = rest
+        .iter()
+        .filter_map(|word| word.chars().next())
+        .flat_map(char::to_uppercase)
+        .map(|initial| format!("{}.", initial))
+        .collect::<Vec<_>>();
+    std::iter::once(last_name)
+        .chain(initials)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Takes the fields `existing` lacks from a later record of the same person.
+fn fill(existing: &mut Application, other: &Application) {
+    existing.priority = existing.priority.or(other.priority);
+    existing.score = existing.score.or(other.score);
+    if existing.status.is_none() {
+        existing.status = other.status.clone();
+    }
+    existing.original |= other.original;
+}
+
+/// Reads every source in order and keeps one application per name and direction.
+/// Fields missing in the first occurrence are taken from later ones. A record without
+/// a direction only stays when the person has no other, otherwise it fills the rest.
+pub async fn merge(sources: &[Box<dyn ApplicantSource>]) -> Result<Vec<Application>> {
+    let mut merged = BTreeMap::<(String, String), Application>::new();
+    for source in sources {
+        let applications = source
+            .applications()
+            .await
+            .with_context(|| format!("Failed to read {}", source.name()))?;
+        for mut application in applications {
+            application.name = normalize_name(&application.name);
+            application.direction = application.direction.trim().to_string();
+            let key = (application.name.clone(), application.direction.clone());
+            match merged.get_mut(&key) {
+                Some(existing) => fill(existing, &application),
+                None => {
+                    merged.insert(key, application);
+                }
+            }
+        }
+    }
+
+    let mut applications = merged.into_values().collect::<Vec<_>>();
+    // Sorted by name, an empty direction comes first among the records of a person.
+    let mut index = 0;
+    while index < applications.len() {
+        let current = &applications[index];
+        let has_directions = current.direction.is_empty()
+            && applications
+                .get(index + 1)
+                .is_some_and(|next| next.name == current.name);
+        if has_directions {
+            let undirected = applications.remove(index);
+            applications[index..]
+                .iter_mut()
+                .take_while(|application| application.name == undirected.name)
+                .for_each(|application| fill(application, &undirected));
+        } else {
+            index += 1;
+        }
+    }
+    Ok(applications)
+}
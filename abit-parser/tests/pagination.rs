@@ -0,0 +1,221 @@
+use abit_parser::fetch::{fixture_name, Fetcher, FixtureFetcher};
+use abit_parser::parser::Options;
+use abit_parser::{Application, Parser};
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+fn parser() -> Parser {
+    Parser::with_fetcher(
+        21,
+        FixtureFetcher::new(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures")),
+    )
+}
+
+fn sorted_names(applications: &[Application]) -> Vec<String> {
+    let mut names = applications
+        .iter()
+        .map(|application| application.name.clone())
+        .collect::<Vec<_>>();
+    names.sort();
+    names
+}
+
+#[test]
+fn fixture_names() {
+    let base = "https://abit-poisk.org.ua/rate2021/direction/";
+    assert_eq!(fixture_name(&format!("{}100003", base)), "100003.html");
+    assert_eq!(
+        fixture_name(&format!("{}100003/?page=2", base)),
+        "100003_page_2.html"
+    );
+}
+
+#[tokio::test]
+async fn single_page() {
+    let applications = parser().get_applications("100001").await.unwrap();
+    assert_eq!(applications.len(), 3);
+    assert_eq!(
+        applications[0],
+        Application {
+            name: "Коваленко А. В.".to_string(),
+            direction: "100001".to_string(),
+            priority: Some(2),
+            score: Some(188.75),
+            status: Some("Допущено".to_string()),
+            original: true,
+        }
+    );
+    assert!(!applications[1].original);
+}
+
+#[tokio::test]
+async fn contract_priority_is_empty() {
+    let applications = parser().get_applications("100003").await.unwrap();
+    let contract = applications
+        .iter()
+        .find(|application| application.name == "Лисенко А. В.")
+        .unwrap();
+    assert_eq!(contract.priority, None);
+    assert_eq!(contract.score, Some(183.75));
+}
+
+#[tokio::test]
+async fn fewer_than_five_pages() {
+    let applications = parser().get_applications("100003").await.unwrap();
+    assert_eq!(applications.len(), 9);
+    assert!(applications
+        .iter()
+        .all(|application| application.direction == "100003"));
+    let names = sorted_names(&applications);
+    names
+        .windows(2)
+        .for_each(|pair| assert_ne!(pair[0], pair[1]));
+}
+
+#[tokio::test]
+async fn many_pages() {
+    let applications = parser().get_applications("100007").await.unwrap();
+    assert_eq!(applications.len(), 21);
+    let names = sorted_names(&applications);
+    // Only the last page has this row, page 1 links to it past the gap in the buttons.
+    assert!(names.contains(&"Поліщук Д. Ю.".to_string()));
+}
+
+#[tokio::test]
+async fn missing_page_fails() {
+    let parser = parser().with_options(quick_retries());
+    assert!(parser.get_applications("100002").await.is_err());
+}
+
+/// Fails the first `failures` requests for every url containing `pattern`.
+struct FlakyFetcher {
+    inner: FixtureFetcher,
+    pattern: &'static str,
+    failures: u32,
+    attempts: Mutex<HashMap<String, u32>>,
+}
+
+impl FlakyFetcher {
+    fn new(pattern: &'static str, failures: u32) -> Self {
+        FlakyFetcher {
+            inner: FixtureFetcher::new(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures")),
+            pattern,
+            failures,
+            attempts: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+#[async_trait]
+impl Fetcher for FlakyFetcher {
+    async fn fetch(&self, url: &str) -> Result<String> {
+        if url.contains(self.pattern) {
+            let mut attempts = self.attempts.lock().unwrap();
+            let attempt = attempts.entry(url.to_string()).or_insert(0);
+            *attempt += 1;
+            if *attempt <= self.failures {
+                bail!("attempt {} failed", attempt);
+            }
+        }
+        self.inner.fetch(url).await
+    }
+}
+
+fn quick_retries() -> Options {
+    Options {
+        retries: 2,
+        backoff: Duration::from_millis(1),
+        ..Options::default()
+    }
+}
+
+#[tokio::test]
+async fn retries_failed_pages() {
+    let parser =
+        Parser::with_fetcher(21, FlakyFetcher::new("page=", 2)).with_options(quick_retries());
+    let report = parser.get_report("100007").await.unwrap();
+    assert!(report.failed.is_empty());
+    assert_eq!(report.applications.len(), 21);
+}
+
+#[tokio::test]
+async fn reports_pages_that_keep_failing() {
+    let parser =
+        Parser::with_fetcher(21, FlakyFetcher::new("page=3", 10)).with_options(quick_retries());
+    let report = parser.get_report("100007").await.unwrap();
+    assert_eq!(report.applications.len(), 18);
+    assert_eq!(report.failed.len(), 1);
+    assert!(report.failed[0].url.ends_with("100007/?page=3"));
+    // Page 1 links to the last page, so nothing after page 3 is lost.
+    assert!(!report.failed[0].last_known);
+    assert!(parser.get_applications("100007").await.is_err());
+}
+
+/// A rating where every page only links to the next one.
+struct ChainFetcher {
+    pages: u32,
+    broken: Option<u32>,
+}
+
+#[async_trait]
+impl Fetcher for ChainFetcher {
+    async fn fetch(&self, url: &str) -> Result<String> {
+        let page = url
+            .rsplit("page=")
+            .next()
+            .and_then(|page| page.parse().ok())
+            .unwrap_or(1);
+        if Some(page) == self.broken {
+            bail!("page {} is broken", page);
+        }
+        let next = if page < self.pages {
+            format!(
+                r#"<div class=""><a data-scroll-on-load="" class="btn btn-default ajax secondary-text" href="?page={}">»</a></div>"#,
+                page + 1
+            )
+        } else {
+            String::new()
+        };
+        Ok(format!(
+            r#"<table><tr><th>ПІБ</th></tr><tr><td><a href="/#search-{0}">Студент {0}</a></td></tr></table>{1}"#,
+            page, next
+        ))
+    }
+}
+
+#[tokio::test]
+async fn follows_next_links_past_255_pages() {
+    let parser = Parser::with_fetcher(
+        21,
+        ChainFetcher {
+            pages: 300,
+            broken: None,
+        },
+    );
+    let applications = parser.get_applications("100300").await.unwrap();
+    assert_eq!(applications.len(), 300);
+    assert!(applications
+        .iter()
+        .any(|application| application.name == "Студент 300"));
+}
+
+#[tokio::test]
+async fn reports_pages_not_reached_after_a_failure() {
+    let parser = Parser::with_fetcher(
+        21,
+        ChainFetcher {
+            pages: 10,
+            broken: Some(5),
+        },
+    )
+    .with_options(quick_retries());
+    let report = parser.get_report("100010").await.unwrap();
+    assert_eq!(report.applications.len(), 4);
+    assert_eq!(report.failed.len(), 1);
+    assert!(report.failed[0].last_known);
+    let error = parser.get_applications("100010").await.unwrap_err();
+    assert!(error.to_string().contains("not reached"));
+}
@@ -0,0 +1,128 @@
+use abit_parser::fetch::FixtureFetcher;
+use abit_parser::source::{self, file_source, normalize_name, AbitPoisk, JsonFile, TableFile};
+use abit_parser::{ApplicantSource, Application, Parser};
+
+const SOURCES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/sources");
+
+fn fixture(name: &str) -> String {
+    format!("{}/{}", SOURCES, name)
+}
+
+#[test]
+fn normalizes_names() {
+    assert_eq!(normalize_name("Шевчук Олег Ігорович"), "Шевчук О. І.");
+    assert_eq!(
+        normalize_name("  КОВАЛЕНКО  анна василівна "),
+        "Коваленко А. В."
+    );
+    assert_eq!(
+        normalize_name("Бондар-СИДОРЕНКО Марія"),
+        "Бондар-Сидоренко М."
+    );
+    assert_eq!(normalize_name("Д’ЯЧЕНКО Ірина"), "Д'яченко І.");
+    assert_eq!(normalize_name("Мельник Т. С."), "Мельник Т. С.");
+    assert_eq!(normalize_name("   "), "");
+}
+
+#[tokio::test]
+async fn reads_csv_by_headers() {
+    let applications = TableFile::new(fixture("edebo.csv"))
+        .applications()
+        .await
+        .unwrap();
+    assert_eq!(applications.len(), 4);
+    assert_eq!(
+        applications[1],
+        Application {
+            name: "Шевчук Олег Ігорович".to_string(),
+            direction: "100001".to_string(),
+            priority: Some(1),
+            score: Some(175.5),
+            status: Some("Зареєстровано".to_string()),
+            original: true,
+        }
+    );
+    assert_eq!(applications[0].score, Some(188.75));
+    assert_eq!(applications[0].priority, None);
+    assert_eq!(applications[2].name, "Бондар-СИДОРЕНКО Марія");
+    // Only the offer code is kept of the offer column, the EDEBO id is not a direction.
+    assert_eq!(applications[0].direction, "100001");
+    assert_eq!(applications[2].direction, "100003");
+    assert_eq!(applications[3].direction, "");
+}
+
+#[tokio::test]
+async fn reads_json_array_and_lines() {
+    let array = JsonFile::new(fixture("applicants.json"))
+        .applications()
+        .await
+        .unwrap();
+    assert_eq!(array.len(), 2);
+    assert_eq!(array[0].direction, "");
+    assert!(array[0].original);
+
+    let lines = file_source(fixture("applicants.jsonl"))
+        .applications()
+        .await
+        .unwrap();
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[1].priority, None);
+}
+
+#[tokio::test]
+async fn missing_file_names_the_source() {
+    let sources = vec![file_source(fixture("missing.csv"))];
+    let error = source::merge(&sources).await.unwrap_err();
+    assert!(error.to_string().contains("missing.csv"));
+}
+
+#[tokio::test]
+async fn merges_and_deduplicates() {
+    let parser = Parser::with_fetcher(
+        21,
+        FixtureFetcher::new(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures")),
+    );
+    let sources: Vec<Box<dyn ApplicantSource>> = vec![
+        Box::new(AbitPoisk::new(parser, vec!["100001".to_string()])),
+        file_source(fixture("edebo.csv")),
+        file_source(fixture("applicants.jsonl")),
+        file_source(fixture("applicants.json")),
+    ];
+    let applications = source::merge(&sources).await.unwrap();
+
+    let keys = applications
+        .iter()
+        .map(|application| (application.name.as_str(), application.direction.as_str()))
+        .collect::<Vec<_>>();
+    let mut sorted = keys.clone();
+    sorted.sort();
+    sorted.dedup();
+    assert_eq!(keys, sorted);
+
+    let find = |name: &str, direction: &str| {
+        applications
+            .iter()
+            .find(|application| application.name == name && application.direction == direction)
+            .unwrap()
+    };
+    // The abit-poisk row comes first, the export only repeats it.
+    let kovalenko = find("Коваленко А. В.", "100001");
+    assert_eq!(kovalenko.priority, Some(2));
+    assert!(kovalenko.original);
+    // The JSON Lines row only adds what the export lacks.
+    let shevchuk = find("Шевчук О. І.", "100001");
+    assert_eq!(shevchuk.score, Some(175.5));
+    // No direction in either JSON file, the later one fills the status.
+    let melnyk = find("Мельник Т. С.", "");
+    assert_eq!(melnyk.status.as_deref(), Some("Рекомендовано"));
+    assert!(melnyk.original);
+    assert_eq!(find("Бондар-Сидоренко М.", "100003").priority, Some(2));
+    // Without a direction the record joins the one from abit-poisk.
+    let bondarenko = applications
+        .iter()
+        .filter(|application| application.name == "Бондаренко О. С.")
+        .collect::<Vec<_>>();
+    assert_eq!(bondarenko.len(), 1);
+    assert_eq!(bondarenko[0].direction, "100001");
+    assert!(bondarenko[0].original);
+}
@@ -0,0 +1,17 @@
+DROP TRIGGER IF EXISTS queue_notify ON queue;
+DROP FUNCTION IF EXISTS notify_status();
+
+DROP TABLE IF EXISTS refresh_sessions;
+DROP TABLE IF EXISTS users;
+DROP TYPE IF EXISTS role;
+
+DROP FUNCTION IF EXISTS register_in_queue(BIGINT, DATE, TIME);
+DROP FUNCTION IF EXISTS is_enrollee_valid(VARCHAR, VARCHAR, VARCHAR);
+DROP FUNCTION IF EXISTS get_intervals(DATE, TIME, INTEGER, INTERVAL);
+DROP FUNCTION IF EXISTS get_relevant_time(DATE, TIME, INTEGER, INTERVAL);
+
+DROP TABLE IF EXISTS teloxide_dialogues;
+DROP TABLE IF EXISTS parsed_names;
+DROP TABLE IF EXISTS queue;
+DROP TYPE IF EXISTS status;
+DROP TABLE IF EXISTS enrollee;
@@ -10,7 +10,11 @@ CREATE TABLE IF NOT EXISTS enrollee (
     notification BOOLEAN NOT NULL DEFAULT FALSE
 );
 
-CREATE TYPE status AS ENUM ('wait', 'inside', 'filled', 'processed', 'absent');
+DO $$ BEGIN
+    CREATE TYPE status AS ENUM ('wait', 'inside', 'filled', 'processed', 'absent');
+EXCEPTION
+    WHEN duplicate_object THEN NULL;
+END $$;
 
 CREATE TABLE IF NOT EXISTS queue (
     enrollee INTEGER REFERENCES enrollee(id) PRIMARY KEY,
@@ -100,7 +104,11 @@ BEGIN
     RETURN exists;
 END $$  LANGUAGE plpgsql;
 
-CREATE TYPE role AS ENUM ('user', 'admin');
+DO $$ BEGIN
+    CREATE TYPE role AS ENUM ('user', 'admin');
+EXCEPTION
+    WHEN duplicate_object THEN NULL;
+END $$;
 
 CREATE TABLE IF NOT EXISTS users (
     id SERIAL PRIMARY KEY,
@@ -112,7 +120,9 @@ CREATE TABLE IF NOT EXISTS users (
 
 CREATE EXTENSION IF NOT EXISTS "uuid-ossp";
 
-SET TIMEZONE='Europe/Kiev';
+DO $$ BEGIN
+    EXECUTE format('ALTER DATABASE %I SET TIMEZONE TO %L', current_database(), 'Europe/Kiev');
+END $$;
 
 CREATE TABLE IF NOT EXISTS refresh_sessions (
     id SERIAL PRIMARY KEY,
@@ -144,16 +154,9 @@ BEGIN
     END
 $$ LANGUAGE plpgsql;
 
+DROP TRIGGER IF EXISTS queue_notify ON queue;
+
 CREATE TRIGGER queue_notify
 AFTER INSERT OR UPDATE OR DELETE ON queue
     FOR EACH ROW EXECUTE PROCEDURE notify_status();
 
-
---import names
-COPY parsed_names(name) FROM 'path' CSV;
-
---export queue
-COPY (
-    SELECT last_name, name, patronymic, date, time, phone_number, username, status, id, chat_id
-    FROM queue JOIN enrollee e on e.chat_id = queue.enrollee
-) TO 'path' DELIMITER ',' CSV HEADER;
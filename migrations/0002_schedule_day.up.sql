@@ -0,0 +1,6 @@
+CREATE TABLE IF NOT EXISTS schedule_day (
+    date DATE PRIMARY KEY,
+    start_time TIME NOT NULL,
+    interval INTEGER NOT NULL CHECK (interval > 0),
+    max_enrollee INTEGER NOT NULL CHECK (max_enrollee > 0)
+);
@@ -0,0 +1,58 @@
+CREATE OR REPLACE FUNCTION get_relevant_time(
+    start_date date,
+    start_time time,
+    max_enrollee integer,
+    wait_time interval
+) RETURNS SETOF time
+AS $$
+    DECLARE start_timestamp timestamp := start_date + start_time;
+BEGIN
+    RETURN QUERY
+        SELECT time::time FROM generate_series(
+            start_timestamp,
+            start_timestamp + (wait_time * (max_enrollee - 1)),
+            wait_time
+        ) AS time WHERE time::time NOT IN (SELECT time FROM queue WHERE date = start_date);
+END $$  LANGUAGE plpgsql;
+
+CREATE OR REPLACE FUNCTION get_intervals(
+    start_date date,
+    start_time time,
+    max_enrollee integer,
+    wait_time interval
+) RETURNS SETOF text
+AS $$
+BEGIN
+    RETURN QUERY
+        SELECT concat(intervals.interval_time, ':00-', intervals.interval_time + 1, ':00') FROM (
+            SELECT COUNT(*), date_part('hour', time) as interval_time FROM get_relevant_time(
+                start_date,
+                start_time,
+                max_enrollee,
+                wait_time
+            ) AS time
+            GROUP BY interval_time
+            ORDER BY interval_time
+        ) as intervals WHERE intervals.count > 0;
+END $$  LANGUAGE plpgsql;
+
+ALTER TABLE schedule_day
+    ADD COLUMN start_time TIME,
+    ADD COLUMN interval INTEGER CHECK (interval > 0),
+    ADD COLUMN max_enrollee INTEGER CHECK (max_enrollee > 0);
+
+UPDATE schedule_day SET start_time = first.start_time, interval = first.interval, max_enrollee = first.max_enrollee
+FROM (
+    SELECT DISTINCT ON (date) date, start_time, interval, max_enrollee
+    FROM schedule_window ORDER BY date, start_time
+) AS first WHERE schedule_day.date = first.date;
+
+DELETE FROM schedule_day WHERE start_time IS NULL;
+
+ALTER TABLE schedule_day
+    ALTER COLUMN start_time SET NOT NULL,
+    ALTER COLUMN interval SET NOT NULL,
+    ALTER COLUMN max_enrollee SET NOT NULL;
+
+DROP TABLE IF EXISTS schedule_break;
+DROP TABLE IF EXISTS schedule_window;
@@ -0,0 +1,25 @@
+CREATE TABLE IF NOT EXISTS schedule_window (
+    id SERIAL PRIMARY KEY,
+    date DATE NOT NULL REFERENCES schedule_day(date) ON DELETE CASCADE,
+    start_time TIME NOT NULL,
+    interval INTEGER NOT NULL CHECK (interval > 0),
+    max_enrollee INTEGER NOT NULL CHECK (max_enrollee > 0)
+);
+
+CREATE TABLE IF NOT EXISTS schedule_break (
+    id SERIAL PRIMARY KEY,
+    date DATE NOT NULL REFERENCES schedule_day(date) ON DELETE CASCADE,
+    start_time TIME NOT NULL,
+    end_time TIME NOT NULL CHECK (end_time > start_time)
+);
+
+INSERT INTO schedule_window (date, start_time, interval, max_enrollee)
+SELECT date, start_time, interval, max_enrollee FROM schedule_day;
+
+ALTER TABLE schedule_day
+    DROP COLUMN start_time,
+    DROP COLUMN interval,
+    DROP COLUMN max_enrollee;
+
+DROP FUNCTION IF EXISTS get_intervals(DATE, TIME, INTEGER, INTERVAL);
+DROP FUNCTION IF EXISTS get_relevant_time(DATE, TIME, INTEGER, INTERVAL);
@@ -0,0 +1,32 @@
+CREATE OR REPLACE FUNCTION notify_status() RETURNS TRIGGER AS $$
+DECLARE
+    record RECORD;
+    count INTEGER;
+BEGIN
+    IF CURRENT_DATE = NEW.date AND OLD.status = 'wait' THEN
+        FOR record IN
+            SELECT * FROM queue WHERE date = CURRENT_DATE AND status = 'wait'
+        LOOP
+            IF record.time >= NEW.time THEN
+                SELECT COUNT(*) INTO count FROM queue WHERE enrollee != record.enrollee AND date = record.date AND status = 'wait' AND time < record.time;
+                IF (SELECT notification FROM enrollee WHERE id = record.enrollee) OR count = 5 OR count = 1 OR count = 0 THEN
+                    PERFORM pg_notify('queue_status', row_to_json(row((SELECT chat_id FROM enrollee WHERE id = record.enrollee), count, record.enrollee))::text);
+                END IF;
+            END IF;
+        END LOOP;
+    END IF;
+        RETURN NULL;
+    END
+$$ LANGUAGE plpgsql;
+
+DROP INDEX IF EXISTS queue_inside_desk_idx;
+DROP INDEX IF EXISTS queue_date_time_idx;
+ALTER TABLE queue DROP COLUMN IF EXISTS desk;
+
+-- Rolling back is lossy: with several desks a slot may have several bookings, only the
+-- one of the lowest enrollee id is kept so the slot can be unique again.
+DELETE FROM queue USING queue AS kept
+    WHERE queue.date = kept.date AND queue.time = kept.time AND queue.enrollee > kept.enrollee;
+ALTER TABLE queue ADD CONSTRAINT queue_date_time_key UNIQUE (date, time);
+
+ALTER TABLE schedule_day DROP COLUMN IF EXISTS desks;
@@ -0,0 +1,31 @@
+ALTER TABLE schedule_day ADD COLUMN IF NOT EXISTS desks INTEGER NOT NULL DEFAULT 1 CHECK (desks > 0);
+
+ALTER TABLE queue DROP CONSTRAINT IF EXISTS queue_date_time_key;
+ALTER TABLE queue ADD COLUMN IF NOT EXISTS desk INTEGER CHECK (desk > 0);
+
+CREATE INDEX IF NOT EXISTS queue_date_time_idx ON queue (date, time);
+CREATE UNIQUE INDEX IF NOT EXISTS queue_inside_desk_idx ON queue (date, desk) WHERE status = 'inside';
+
+CREATE OR REPLACE FUNCTION notify_status() RETURNS TRIGGER AS $$
+DECLARE
+    record RECORD;
+    count INTEGER;
+BEGIN
+    IF TG_OP = 'UPDATE' AND NEW.status = 'inside' AND OLD.status IS DISTINCT FROM 'inside' AND NEW.desk IS NOT NULL THEN
+        PERFORM pg_notify('queue_status', row_to_json(row((SELECT chat_id FROM enrollee WHERE id = NEW.enrollee), 0, NEW.enrollee, NEW.desk))::text);
+    END IF;
+    IF CURRENT_DATE = NEW.date AND OLD.status = 'wait' THEN
+        FOR record IN
+            SELECT * FROM queue WHERE date = CURRENT_DATE AND status = 'wait'
+        LOOP
+            IF record.time >= NEW.time THEN
+                SELECT COUNT(*) INTO count FROM queue WHERE enrollee != record.enrollee AND date = record.date AND status = 'wait' AND time < record.time;
+                IF (SELECT notification FROM enrollee WHERE id = record.enrollee) OR count = 5 OR count = 1 OR count = 0 THEN
+                    PERFORM pg_notify('queue_status', row_to_json(row((SELECT chat_id FROM enrollee WHERE id = record.enrollee), count, record.enrollee))::text);
+                END IF;
+            END IF;
+        END LOOP;
+    END IF;
+        RETURN NULL;
+    END
+$$ LANGUAGE plpgsql;
@@ -0,0 +1,8 @@
+CREATE TABLE IF NOT EXISTS sent_reminder (
+    enrollee INTEGER NOT NULL REFERENCES enrollee(id) ON DELETE CASCADE,
+    kind VARCHAR(32) NOT NULL,
+    date DATE NOT NULL,
+    time TIME NOT NULL,
+    sent_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY (enrollee, kind, date, time)
+);
@@ -0,0 +1 @@
+ALTER TABLE enrollee DROP COLUMN IF EXISTS language;
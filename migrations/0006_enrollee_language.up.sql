@@ -0,0 +1 @@
+ALTER TABLE enrollee ADD COLUMN IF NOT EXISTS language VARCHAR(8);
@@ -0,0 +1,3 @@
+DROP TRIGGER IF EXISTS enrollee_events ON enrollee;
+DROP TRIGGER IF EXISTS queue_events ON queue;
+DROP FUNCTION IF EXISTS notify_queue_event();
@@ -0,0 +1,50 @@
+CREATE OR REPLACE FUNCTION notify_queue_event() RETURNS TRIGGER AS $$
+DECLARE
+    kind TEXT;
+    record RECORD;
+BEGIN
+    IF TG_TABLE_NAME = 'enrollee' THEN
+        PERFORM pg_notify('queue_events', json_build_object(
+            'kind', 'edited', 'enrollee', NEW.id, 'date', queue.date, 'time', queue.time,
+            'status', queue.status, 'desk', queue.desk
+        )::text)
+        FROM queue WHERE queue.enrollee = NEW.id;
+        RETURN NULL;
+    END IF;
+    IF TG_OP = 'INSERT' THEN
+        kind := 'booked';
+        record := NEW;
+    ELSIF TG_OP = 'DELETE' THEN
+        kind := 'cancelled';
+        record := OLD;
+    ELSIF NEW.status IS DISTINCT FROM OLD.status OR NEW.desk IS DISTINCT FROM OLD.desk THEN
+        kind := 'status';
+        record := NEW;
+    ELSIF NEW.date IS DISTINCT FROM OLD.date OR NEW.time IS DISTINCT FROM OLD.time THEN
+        kind := 'edited';
+        record := NEW;
+    ELSE
+        RETURN NULL;
+    END IF;
+    PERFORM pg_notify('queue_events', json_build_object(
+        'kind', kind, 'enrollee', record.enrollee, 'date', record.date, 'time', record.time,
+        'status', record.status, 'desk', record.desk
+    )::text);
+    RETURN NULL;
+END
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS queue_events ON queue;
+
+CREATE TRIGGER queue_events
+AFTER INSERT OR UPDATE OR DELETE ON queue
+    FOR EACH ROW EXECUTE PROCEDURE notify_queue_event();
+
+DROP TRIGGER IF EXISTS enrollee_events ON enrollee;
+
+CREATE TRIGGER enrollee_events
+AFTER UPDATE OF last_name, name, patronymic, username, phone_number ON enrollee
+    FOR EACH ROW
+    WHEN ((OLD.last_name, OLD.name, OLD.patronymic, OLD.username, OLD.phone_number)
+        IS DISTINCT FROM (NEW.last_name, NEW.name, NEW.patronymic, NEW.username, NEW.phone_number))
+    EXECUTE PROCEDURE notify_queue_event();
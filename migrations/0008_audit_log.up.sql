@@ -0,0 +1,13 @@
+CREATE TABLE IF NOT EXISTS audit_log (
+    id BIGSERIAL PRIMARY KEY,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    actor_id INTEGER REFERENCES users(id) ON DELETE SET NULL,
+    actor VARCHAR(16) NOT NULL,
+    action VARCHAR(32) NOT NULL,
+    enrollee INTEGER,
+    before JSONB,
+    after JSONB
+);
+
+CREATE INDEX IF NOT EXISTS audit_log_created_at_idx ON audit_log (created_at);
+CREATE INDEX IF NOT EXISTS audit_log_enrollee_idx ON audit_log (enrollee);
@@ -0,0 +1,7 @@
+ALTER TABLE users ALTER COLUMN role DROP DEFAULT;
+ALTER TABLE users ALTER COLUMN role TYPE VARCHAR(16);
+UPDATE users SET role = 'user' WHERE role IN ('operator', 'supervisor');
+DROP TYPE role;
+CREATE TYPE role AS ENUM ('user', 'admin');
+ALTER TABLE users ALTER COLUMN role TYPE role USING role::role;
+ALTER TABLE users ALTER COLUMN role SET DEFAULT 'user'::role;
@@ -0,0 +1,2 @@
+ALTER TYPE role ADD VALUE IF NOT EXISTS 'operator' BEFORE 'admin';
+ALTER TYPE role ADD VALUE IF NOT EXISTS 'supervisor' BEFORE 'admin';
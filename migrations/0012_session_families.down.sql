@@ -0,0 +1,6 @@
+DELETE FROM refresh_sessions WHERE rotated_at IS NOT NULL;
+DROP INDEX IF EXISTS refresh_sessions_user_id_idx;
+DROP INDEX IF EXISTS refresh_sessions_family_idx;
+DROP INDEX IF EXISTS refresh_sessions_token_idx;
+ALTER TABLE refresh_sessions DROP COLUMN IF EXISTS rotated_at;
+ALTER TABLE refresh_sessions DROP COLUMN IF EXISTS family;
@@ -0,0 +1,8 @@
+-- A session keeps its family through refresh token rotation, rotated tokens stay around
+-- until the family expires so that a replayed one can be recognised
+ALTER TABLE refresh_sessions ADD COLUMN IF NOT EXISTS family UUID NOT NULL DEFAULT uuid_generate_v4();
+ALTER TABLE refresh_sessions ADD COLUMN IF NOT EXISTS rotated_at TIMESTAMPTZ;
+
+CREATE UNIQUE INDEX IF NOT EXISTS refresh_sessions_token_idx ON refresh_sessions (refresh_token);
+CREATE INDEX IF NOT EXISTS refresh_sessions_family_idx ON refresh_sessions (family);
+CREATE INDEX IF NOT EXISTS refresh_sessions_user_id_idx ON refresh_sessions (user_id);
@@ -0,0 +1,6 @@
+-- Keyed by `user:<username>` and `ip:<address>`
+CREATE TABLE IF NOT EXISTS login_failure (
+    key TEXT PRIMARY KEY,
+    failures INTEGER NOT NULL,
+    last_failure TIMESTAMPTZ NOT NULL
+);
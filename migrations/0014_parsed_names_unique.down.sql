@@ -0,0 +1,2 @@
+DROP INDEX IF EXISTS parsed_names_name_idx;
+ALTER TABLE parsed_names ALTER COLUMN name DROP NOT NULL;
@@ -0,0 +1,6 @@
+-- abit-parser imports names incrementally, so each one is kept once
+DELETE FROM parsed_names a USING parsed_names b
+    WHERE a.ctid < b.ctid AND a.name = b.name;
+DELETE FROM parsed_names WHERE name IS NULL;
+ALTER TABLE parsed_names ALTER COLUMN name SET NOT NULL;
+CREATE UNIQUE INDEX IF NOT EXISTS parsed_names_name_idx ON parsed_names (name);
@@ -0,0 +1,12 @@
+-- Every row abit-parser saw, parsed_names keeps the distinct names for is_enrollee_valid
+CREATE TABLE IF NOT EXISTS applications (
+    id SERIAL PRIMARY KEY,
+    name VARCHAR(255) NOT NULL,
+    direction VARCHAR(16) NOT NULL,
+    priority SMALLINT,
+    score DOUBLE PRECISION,
+    status VARCHAR(255),
+    original BOOLEAN NOT NULL DEFAULT false
+);
+
+CREATE INDEX IF NOT EXISTS applications_name_idx ON applications (name);
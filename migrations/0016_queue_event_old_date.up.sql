@@ -0,0 +1,43 @@
+CREATE OR REPLACE FUNCTION notify_queue_event() RETURNS TRIGGER AS $$
+DECLARE
+    kind TEXT;
+    record RECORD;
+    old_date DATE;
+BEGIN
+    IF TG_TABLE_NAME = 'enrollee' THEN
+        PERFORM pg_notify('queue_events', json_build_object(
+            'kind', 'edited', 'enrollee', NEW.id, 'date', queue.date, 'time', queue.time,
+            'status', queue.status, 'desk', queue.desk
+        )::text)
+        FROM queue WHERE queue.enrollee = NEW.id;
+        RETURN NULL;
+    END IF;
+    IF TG_OP = 'INSERT' THEN
+        kind := 'booked';
+        record := NEW;
+    ELSIF TG_OP = 'DELETE' THEN
+        kind := 'cancelled';
+        record := OLD;
+    ELSIF NEW.status IS DISTINCT FROM OLD.status OR NEW.desk IS DISTINCT FROM OLD.desk THEN
+        kind := 'status';
+        record := NEW;
+    ELSIF NEW.date IS DISTINCT FROM OLD.date OR NEW.time IS DISTINCT FROM OLD.time THEN
+        kind := 'edited';
+        record := NEW;
+    ELSE
+        RETURN NULL;
+    END IF;
+    -- Boards of the previous date have to drop the booking
+    IF TG_OP = 'UPDATE' THEN
+        IF NEW.date IS DISTINCT FROM OLD.date THEN
+            old_date := OLD.date;
+        END IF;
+    END IF;
+    PERFORM pg_notify('queue_events', json_build_object(
+        'kind', kind, 'enrollee', record.enrollee, 'date', record.date, 'time', record.time,
+        'status', record.status, 'desk', record.desk, 'oldDate', old_date
+    )::text);
+    RETURN NULL;
+END
+$$ LANGUAGE plpgsql;
+
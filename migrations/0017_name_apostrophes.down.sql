@@ -0,0 +1,21 @@
+CREATE OR REPLACE FUNCTION is_enrollee_valid(
+    last_name_t VARCHAR(255),
+    name_t VARCHAR(255),
+    patronymic_t VARCHAR(255)
+) RETURNS bool
+AS $$
+    DECLARE
+        name_t VARCHAR(1) := left(name_t, 1);
+        patronymic_t VARCHAR(1) := left(patronymic_t, 1);
+BEGIN
+    RETURN (
+        SELECT NOT
+            exists(SELECT 1 FROM enrollee WHERE left(name, 1) = name_t AND left(patronymic, 1) = patronymic_t AND last_name = last_name_t)
+            AND
+            exists(SELECT 1 FROM parsed_names WHERE name = concat_ws(' ', last_name_t, name_t || '.', patronymic_t || '.'))
+    );
+END $$  LANGUAGE plpgsql;
+
+DROP INDEX IF EXISTS applications_apostrophes_idx;
+DROP INDEX IF EXISTS parsed_names_apostrophes_idx;
+DROP FUNCTION IF EXISTS normalize_apostrophes(TEXT);
@@ -0,0 +1,27 @@
+-- abit-parser writes every apostrophe of a name as ', enrollees type ’ or ʼ on a Ukrainian
+-- keyboard and names imported before may have any of them, so both sides are compared alike
+CREATE OR REPLACE FUNCTION normalize_apostrophes(name TEXT) RETURNS TEXT AS $$
+    SELECT translate(name, '’ʼ`', '''''''');
+$$ LANGUAGE sql IMMUTABLE;
+
+CREATE INDEX IF NOT EXISTS parsed_names_apostrophes_idx ON parsed_names (normalize_apostrophes(name));
+CREATE INDEX IF NOT EXISTS applications_apostrophes_idx ON applications (normalize_apostrophes(name));
+
+CREATE OR REPLACE FUNCTION is_enrollee_valid(
+    last_name_t VARCHAR(255),
+    name_t VARCHAR(255),
+    patronymic_t VARCHAR(255)
+) RETURNS bool
+AS $$
+    DECLARE
+        name_t VARCHAR(1) := left(name_t, 1);
+        patronymic_t VARCHAR(1) := left(patronymic_t, 1);
+        last_name_t VARCHAR(255) := normalize_apostrophes(last_name_t);
+BEGIN
+    RETURN (
+        SELECT NOT
+            exists(SELECT 1 FROM enrollee WHERE left(name, 1) = name_t AND left(patronymic, 1) = patronymic_t AND normalize_apostrophes(last_name) = last_name_t)
+            AND
+            exists(SELECT 1 FROM parsed_names WHERE normalize_apostrophes(name) = concat_ws(' ', last_name_t, name_t || '.', patronymic_t || '.'))
+    );
+END $$  LANGUAGE plpgsql;
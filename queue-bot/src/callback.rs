@@ -0,0 +1,223 @@
+use anyhow::Result;
+use chrono::{NaiveDate, NaiveTime, Timelike};
+use queue_core::i18n::Language;
+use queue_core::queue::BookingError;
+use queue_core::tr;
+use std::fmt;
+use std::str::FromStr;
+use teloxide::prelude::*;
+use teloxide::types::InlineKeyboardMarkup;
+
+use crate::database::Database;
+use crate::language;
+use crate::queue::Queue;
+
+const DATE_FORMAT: &str = "%Y-%m-%d";
+const TIME_FORMAT: &str = "%H:%M";
+
+/// Data attached to the inline buttons of the booking flow.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Callback {
+    Days,
+    Day(NaiveDate),
+    Interval(NaiveDate, u32),
+    Time(NaiveDate, NaiveTime),
+    Language(Language),
+}
+
+impl fmt::Display for Callback {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Callback::Days => write!(f, "days"),
+            Callback::Day(date) => write!(f, "day:{}", date.format(DATE_FORMAT)),
+            Callback::Interval(date, hour) => {
+                write!(f, "interval:{}:{}", date.format(DATE_FORMAT), hour)
+            }
+            Callback::Time(date, time) => write!(
+                f,
+                "time:{}:{}",
+                date.format(DATE_FORMAT),
+                time.format(TIME_FORMAT)
+            ),
+            Callback::Language(language) => write!(f, "language:{}", language),
+        }
+    }
+}
+
+impl FromStr for Callback {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.splitn(3, ':');
+        let kind = parts.next().unwrap_or_default();
+        if kind == "days" {
+            return Ok(Callback::Days);
+        }
+        if kind == "language" {
+            return Ok(Callback::Language(
+                parts.next().unwrap_or_default().parse()?,
+            ));
+        }
+        let date = NaiveDate::parse_from_str(parts.next().unwrap_or_default(), DATE_FORMAT)?;
+        let rest = parts.next();
+        Ok(match (kind, rest) {
+            ("day", None) => Callback::Day(date),
+            ("interval", Some(hour)) => Callback::Interval(date, hour.parse()?),
+            ("time", Some(time)) => {
+                Callback::Time(date, NaiveTime::parse_from_str(time, TIME_FORMAT)?)
+            }
+            _ => anyhow::bail!("Unknown callback data: {}", s),
+        })
+    }
+}
+
+pub async fn handle(cx: UpdateWithCx<AutoSend<Bot>, CallbackQuery>) -> Result<()> {
+    let query = &cx.update;
+    let callback = query
+        .data
+        .as_deref()
+        .and_then(|data| data.parse::<Callback>().ok());
+    let (message, callback) = match (&query.message, callback) {
+        (Some(message), Some(callback)) => (message, callback),
+        _ => {
+            cx.requester.answer_callback_query(&query.id).await?;
+            return Ok(());
+        }
+    };
+    let chat_id = message.chat.id;
+    let language = language::of_user(chat_id, Some(&query.from)).await;
+    match react(chat_id, callback, language).await {
+        Ok((text, keyboard)) => {
+            cx.requester.answer_callback_query(&query.id).await?;
+            cx.requester
+                .edit_message_text(chat_id, message.id, text)
+                .reply_markup(keyboard)
+                .await?;
+        }
+        Err(error) => {
+            cx.requester
+                .answer_callback_query(&query.id)
+                .text(error.to_string())
+                .show_alert(true)
+                .await?;
+        }
+    }
+    Ok(())
+}
+
+async fn react(
+    chat_id: i64,
+    callback: Callback,
+    language: Language,
+) -> Result<(String, InlineKeyboardMarkup)> {
+    let queue = Queue::global();
+    match callback {
+        Callback::Days => Ok((
+            tr!(language, "choose-day"),
+            queue.get_days_keyboard(language).await?,
+        )),
+        Callback::Day(date) => Ok((
+            tr!(language, "choose-interval"),
+            queue.get_intervals_keyboard(date, language).await?,
+        )),
+        Callback::Interval(date, hour) => Ok((
+            tr!(language, "choose-time"),
+            queue
+                .get_relevant_time_keyboard(date, hour, language)
+                .await?,
+        )),
+        Callback::Time(date, time) => book(chat_id, date, time, language).await,
+        Callback::Language(language) => change_language(chat_id, language).await,
+    }
+}
+
+pub fn get_language_keyboard() -> InlineKeyboardMarkup {
+    InlineKeyboardMarkup::default().append_row(
+        Language::ALL
+            .iter()
+            .map(|language| {
+                Queue::button(
+                    tr!(*language, "language-name"),
+                    Callback::Language(*language),
+                )
+            })
+            .collect::<Vec<_>>(),
+    )
+}
+
+async fn change_language(
+    chat_id: i64,
+    language: Language,
+) -> Result<(String, InlineKeyboardMarkup)> {
+    let changed = Database::global()
+        .set_language(chat_id, language)
+        .await
+        .map_err(|error| {
+            log::error!("Database error: {}", error);
+            anyhow::anyhow!(tr!(language, "language-failed"))
+        })?;
+    if !changed {
+        anyhow::bail!(tr!(language, "not-registered"));
+    }
+    Ok((tr!(language, "language-changed"), get_language_keyboard()))
+}
+
+async fn book(
+    chat_id: i64,
+    date: NaiveDate,
+    time: NaiveTime,
+    language: Language,
+) -> Result<(String, InlineKeyboardMarkup)> {
+    let queue = Queue::global();
+    if Database::global().is_banned(chat_id).await? {
+        anyhow::bail!(tr!(language, "banned"));
+    }
+    match Database::global()
+        .register_in_queue(chat_id, date, time)
+        .await
+    {
+        Ok(old_record) => {
+            let id = if old_record {
+                "booking-moved"
+            } else {
+                "booking-created"
+            };
+            Ok((
+                tr!(
+                    language,
+                    id,
+                    date = date.to_string(),
+                    time = time.format(TIME_FORMAT).to_string()
+                ),
+                InlineKeyboardMarkup::default().append_row(vec![Queue::button(
+                    tr!(language, "change-booking"),
+                    Callback::Days,
+                )]),
+            ))
+        }
+        Err(BookingError::DayNotFound) => Ok((
+            tr!(language, "day-not-found"),
+            queue.get_days_keyboard(language).await?,
+        )),
+        Err(BookingError::PastDate) => Ok((
+            tr!(language, "booking-past-date"),
+            queue.get_days_keyboard(language).await?,
+        )),
+        Err(BookingError::Occupied) => Ok((
+            tr!(language, "booking-occupied"),
+            queue
+                .get_relevant_time_keyboard(date, time.hour(), language)
+                .await?,
+        )),
+        Err(BookingError::UnknownTime) => Ok((
+            tr!(language, "booking-unknown-time"),
+            queue
+                .get_relevant_time_keyboard(date, time.hour(), language)
+                .await?,
+        )),
+        Err(BookingError::Database(error)) => {
+            log::error!("Database error: {}", error);
+            anyhow::bail!(tr!(language, "booking-failed"))
+        }
+    }
+}
@@ -0,0 +1,172 @@
+use queue_core::enrollee::Status;
+use queue_core::i18n::Language;
+use queue_core::tr;
+use teloxide::prelude::*;
+use teloxide::types::{BotCommand as MenuCommand, ParseMode};
+use teloxide::utils::command::BotCommand;
+use teloxide::RequestError;
+
+use crate::callback;
+use crate::database::Database;
+use crate::language;
+use crate::queue::Queue;
+
+#[derive(BotCommand, PartialEq, Debug)]
+#[command(rename = "lowercase")]
+pub enum Command {
+    Start,
+    #[command(rename = "my_booking")]
+    MyBooking,
+    Cancel,
+    #[command(rename = "toggle_notification")]
+    ToggleNotification,
+    Language,
+}
+
+impl Command {
+    const ALL: [Command; 5] = [
+        Command::Start,
+        Command::MyBooking,
+        Command::Cancel,
+        Command::ToggleNotification,
+        Command::Language,
+    ];
+
+    /// Name as renamed above and the message describing the command in the menu.
+    fn menu_entry(&self) -> (&'static str, &'static str) {
+        match self {
+            Command::Start => ("start", "menu-start"),
+            Command::MyBooking => ("my_booking", "menu-my-booking"),
+            Command::Cancel => ("cancel", "menu-cancel"),
+            Command::ToggleNotification => ("toggle_notification", "menu-toggle-notification"),
+            Command::Language => ("language", "menu-language"),
+        }
+    }
+}
+
+/// Telegram shows the menu before the user picks a language, so it uses the default one.
+pub async fn set_menu(bot: &AutoSend<Bot>) -> Result<(), RequestError> {
+    let language = Language::default();
+    let commands = Command::ALL
+        .iter()
+        .map(|command| {
+            let (name, id) = command.menu_entry();
+            MenuCommand::new(name, tr!(language, id))
+        })
+        .collect::<Vec<_>>();
+    bot.set_my_commands(commands).await?;
+    Ok(())
+}
+
+pub fn status_text(status: Status, language: Language) -> String {
+    let id = match status {
+        Status::Wait => "status-wait",
+        Status::Inside => "status-inside",
+        Status::Filled => "status-filled",
+        Status::Processed => "status-processed",
+        Status::Absent => "status-absent",
+    };
+    tr!(language, id)
+}
+
+pub async fn my_booking(cx: &UpdateWithCx<AutoSend<Bot>, Message>) -> Result<(), RequestError> {
+    let database = Database::global();
+    let language = language::of_message(cx).await;
+    let enrollee = match database.get_enrollee_id(cx.update.chat_id()).await {
+        Ok(Some(enrollee)) => enrollee,
+        Ok(None) => {
+            cx.answer(tr!(language, "not-registered")).await?;
+            return Ok(());
+        }
+        Err(error) => {
+            cx.answer(tr!(language, "command-error")).await?;
+            log::error!("Database error: {}", error);
+            return Ok(());
+        }
+    };
+    match database.get_booking(enrollee).await {
+        Ok(Some(booking)) => {
+            let status = match booking.desk.filter(|_| booking.status == Status::Inside) {
+                Some(desk) => format!(
+                    "{}\n{}",
+                    status_text(booking.status, language),
+                    tr!(language, "booking-desk", desk = desk)
+                ),
+                None => status_text(booking.status, language),
+            };
+            cx.answer(tr!(
+                language,
+                "booking-info",
+                date = booking.date.format("%d.%m.%Y").to_string(),
+                time = booking.time.format("%H:%M").to_string(),
+                status = status,
+                number = enrollee
+            ))
+            .parse_mode(ParseMode::Html)
+            .await?;
+        }
+        Ok(None) => {
+            cx.answer(tr!(language, "no-booking")).await?;
+        }
+        Err(error) => {
+            cx.answer(tr!(language, "command-error")).await?;
+            log::error!("Database error: {}", error);
+        }
+    }
+    Ok(())
+}
+
+pub async fn cancel(cx: &UpdateWithCx<AutoSend<Bot>, Message>) -> Result<(), RequestError> {
+    let database = Database::global();
+    let language = language::of_message(cx).await;
+    let result = match database.get_enrollee_id(cx.update.chat_id()).await {
+        Ok(Some(enrollee)) => database.cancel_booking(enrollee).await.map(Some),
+        Ok(None) => Ok(None),
+        Err(error) => Err(error),
+    };
+    match result {
+        Ok(Some(true)) => match Queue::global().get_days_keyboard(language).await {
+            Ok(keyboard) => {
+                cx.answer(tr!(language, "booking-cancelled-choose-day"))
+                    .reply_markup(keyboard)
+                    .await?;
+            }
+            Err(_) => {
+                cx.answer(tr!(language, "booking-cancelled")).await?;
+            }
+        },
+        Ok(Some(false)) => {
+            cx.answer(tr!(language, "nothing-to-cancel")).await?;
+        }
+        Ok(None) => {
+            cx.answer(tr!(language, "not-registered")).await?;
+        }
+        Err(error) => {
+            cx.answer(tr!(language, "cancel-failed")).await?;
+            log::error!("Database error: {}", error);
+        }
+    }
+    Ok(())
+}
+
+pub async fn language(cx: &UpdateWithCx<AutoSend<Bot>, Message>) -> Result<(), RequestError> {
+    let language = language::of_message(cx).await;
+    cx.answer(tr!(language, "choose-language"))
+        .reply_markup(callback::get_language_keyboard())
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn menu_names_parse_to_their_commands() {
+        for command in Command::ALL {
+            let (name, _) = command.menu_entry();
+            let parsed = Command::parse(&format!("/{}", name), "bot").unwrap();
+            assert_eq!(parsed, command);
+        }
+    }
+}
@@ -1,5 +1,6 @@
 use anyhow::Result;
 use chrono::{NaiveDate, NaiveTime};
+use queue_core::schedule::{time_format, ScheduleDay, Window};
 use serde::Deserialize;
 use serde::Serialize;
 use std::collections::BTreeMap;
@@ -7,11 +8,10 @@ use std::fs;
 use std::fs::OpenOptions;
 use std::path::Path;
 
-use crate::queue::Schedule;
+use crate::database::Database;
 use crate::{database, queue};
 
 pub mod date_format;
-pub mod time_format;
 
 #[derive(Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -19,27 +19,48 @@ pub struct Config {
     pub token: String,
     pub database_url: String,
     pub max_connections: u32,
-    #[serde(with = "date_format")]
-    pub schedule: BTreeMap<NaiveDate, Schedule>,
+    #[serde(
+        default,
+        with = "date_format",
+        skip_serializing_if = "BTreeMap::is_empty"
+    )]
+    pub schedule: BTreeMap<NaiveDate, Window>,
     pub post: String,
+    #[serde(default)]
+    pub reminders: Reminders,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Reminders {
+    pub evening: bool,
+    #[serde(with = "time_format")]
+    pub evening_time: NaiveTime,
+    pub minutes_before: Vec<u32>,
+    /// Seconds between checks for due reminders, at least 1.
+    pub check_interval: u64,
+}
+
+impl Default for Reminders {
+    fn default() -> Self {
+        Reminders {
+            evening: true,
+            evening_time: NaiveTime::from_hms_opt(18, 0, 0).unwrap(),
+            minutes_before: vec![60],
+            check_interval: 60,
+        }
+    }
 }
 
 impl Default for Config {
     fn default() -> Self {
-        let mut map = BTreeMap::new();
-        let date = NaiveDate::from_ymd(2021, 8, 1);
-        let schedule = Schedule {
-            start_time: NaiveTime::from_hms(10, 0, 0),
-            interval: 30,
-            max_enrollee: 50,
-        };
-        map.insert(date, schedule);
         Config {
             token: "".to_string(),
             database_url: "".to_string(),
             max_connections: 5,
-            schedule: map,
+            schedule: BTreeMap::new(),
             post: "".to_string(),
+            reminders: Reminders::default(),
         }
     }
 }
@@ -54,6 +75,7 @@ impl Config {
             let file = OpenOptions::new()
                 .create(true)
                 .write(true)
+                .truncate(true)
                 .open(config_path)?;
 
             let config = Self::default();
@@ -62,12 +84,30 @@ impl Config {
         }
 
         let config_file = OpenOptions::new().read(true).open(config_path)?;
-        serde_json::from_reader(&config_file).map_err(|error| anyhow::anyhow!(error))
+        let config: Config = serde_json::from_reader(&config_file)?;
+        if config.reminders.check_interval == 0 {
+            anyhow::bail!("reminders.checkInterval must be at least 1 second");
+        }
+        Ok(config)
     }
 
     pub async fn initialize_data(self) -> Result<()> {
         database::initialize(self.max_connections, &self.database_url, self.post.clone()).await?;
-        queue::initialize(self.schedule).await?;
+        if !self.schedule.is_empty() {
+            log::warn!("Schedule in config is deprecated, use the schedule_day table instead");
+        }
+        for (date, window) in self.schedule {
+            if Database::global()
+                .import_schedule_day(&ScheduleDay {
+                    date,
+                    schedule: window.into(),
+                })
+                .await?
+            {
+                log::info!("Imported schedule for {} from config", date);
+            }
+        }
+        queue::initialize().await?;
         Ok(())
     }
 }
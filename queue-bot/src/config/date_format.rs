@@ -1,12 +1,11 @@
 use chrono::NaiveDate;
+use queue_core::schedule::Window;
 use serde::{Deserialize, Deserializer, Serializer};
 use std::collections::BTreeMap;
 
-use crate::queue::Schedule;
+const FORMAT: &str = "%Y-%m-%d";
 
-const FORMAT: &'static str = "%Y-%m-%d";
-
-pub fn serialize<S>(map: &BTreeMap<NaiveDate, Schedule>, serializer: S) -> Result<S::Ok, S::Error>
+pub fn serialize<S>(map: &BTreeMap<NaiveDate, Window>, serializer: S) -> Result<S::Ok, S::Error>
 where
     S: Serializer,
 {
@@ -16,11 +15,11 @@ where
     serializer.collect_map(map)
 }
 
-pub fn deserialize<'de, D>(deserializer: D) -> Result<BTreeMap<NaiveDate, Schedule>, D::Error>
+pub fn deserialize<'de, D>(deserializer: D) -> Result<BTreeMap<NaiveDate, Window>, D::Error>
 where
     D: Deserializer<'de>,
 {
-    let v = BTreeMap::<String, Schedule>::deserialize(deserializer)?;
+    let v = BTreeMap::<String, Window>::deserialize(deserializer)?;
     v.into_iter()
         .map(|(k, v)| {
             Ok((
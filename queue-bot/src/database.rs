@@ -1,21 +1,25 @@
 use anyhow::Result;
-use chrono::{Duration, NaiveDate, NaiveTime};
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
 use futures::future::BoxFuture;
 use once_cell::sync::OnceCell;
+use queue_core::enrollee::{self, NewEnrollee};
+use queue_core::i18n::Language;
+use queue_core::migrate;
+use queue_core::queue::{self, Booking, BookingError};
+use queue_core::schedule::{self, Schedule, ScheduleDay};
 use serde::de::DeserializeOwned;
 use serde::Serialize;
-use sqlx::postgres::types::PgInterval;
 use sqlx::postgres::PgPoolOptions;
 use sqlx::{Executor, PgPool, Row};
-use std::convert::TryFrom;
 use std::fmt::{Debug, Display};
 use std::sync::Arc;
 use teloxide::dispatching::dialogue::serializer::Json;
 use teloxide::dispatching::dialogue::{Serializer, Storage};
 
-use crate::user::Enrollee;
+use crate::database::reminder::DueReminder;
 
 pub mod notifier;
+pub mod reminder;
 
 static INSTANCE: OnceCell<Database<Json>> = OnceCell::new();
 
@@ -26,12 +30,14 @@ pub struct Database<S> {
 }
 
 pub async fn initialize(max_connections: u32, url: &str, post: String) -> Result<()> {
+    let pool = PgPoolOptions::new()
+        .max_connections(max_connections)
+        .connect(url)
+        .await?;
+    migrate::run(&pool).await?;
     INSTANCE
         .set(Database {
-            pool: PgPoolOptions::new()
-                .max_connections(max_connections)
-                .connect(url)
-                .await?,
+            pool,
             serializer: Json,
             post,
         })
@@ -43,25 +49,8 @@ impl Database<Json> {
         INSTANCE.get().expect("Pool isn't initialized")
     }
 
-    pub async fn register(&self, enrollee: Enrollee) -> Result<i32> {
-        sqlx::query("INSERT INTO enrollee (chat_id, username, name, patronymic, last_name, phone_number) VALUES ($1,$2,$3,$4,$5,$6) RETURNING id")
-            .bind(enrollee.chat_id)
-            .bind(enrollee.username)
-            .bind(enrollee.name)
-            .bind(enrollee.patronymic)
-            .bind(enrollee.last_name)
-            .bind(enrollee.phone_number)
-            .fetch_one(&self.pool)
-            .await
-            .map_err(|error| anyhow::anyhow!(error))
-            .map(|row| row.get(0))
-    }
-
-    pub async fn get_occupied_time(
-        &self,
-        _interval: (NaiveTime, NaiveTime),
-    ) -> Result<Vec<NaiveTime>> {
-        unimplemented!()
+    pub async fn register(&self, enrollee: NewEnrollee) -> Result<i32> {
+        enrollee::register(&self.pool, enrollee).await
     }
 
     pub async fn is_banned(&self, id: i64) -> Result<bool> {
@@ -79,70 +68,27 @@ impl Database<Json> {
         name: &str,
         patronymic: &str,
     ) -> Result<bool> {
-        sqlx::query("SELECT * FROM is_enrollee_valid($1, $2, $3)")
-            .bind(last_name)
-            .bind(name)
-            .bind(patronymic)
-            .fetch_one(&self.pool)
-            .await
-            .map_err(|error| anyhow::anyhow!(error))
-            .map(|row| row.get(0))
+        enrollee::is_enrollee_valid(&self.pool, last_name, name, patronymic).await
     }
 
-    pub async fn get_intervals(
-        &self,
-        date: NaiveDate,
-        time: NaiveTime,
-        max_enrollee: u16,
-        interval: u16,
-    ) -> Result<Vec<String>> {
-        let interval = PgInterval::try_from(Duration::minutes(interval as i64))
-            .map_err(|error| anyhow::anyhow!(error))?;
-        sqlx::query("SELECT * FROM get_intervals($1,$2,$3,$4)")
-            .bind(date)
-            .bind(time)
-            .bind(max_enrollee as i32)
-            .bind(interval)
-            .fetch_all(&self.pool)
-            .await
-            .map_err(|error| anyhow::anyhow!(error))
-            .map(|intervals| intervals.iter().map(|row| row.get(0)).collect())
+    pub async fn get_schedule(&self, date: NaiveDate) -> Result<Option<Schedule>> {
+        schedule::get(&mut *self.pool.acquire().await?, date).await
     }
 
-    pub async fn get_intervals_between(
-        &self,
-        date: NaiveDate,
-        time: NaiveTime,
-        max_enrollee: u16,
-        interval: u16,
-        first_time: NaiveTime,
-        second_time: NaiveTime,
-    ) -> Result<Vec<String>> {
-        let interval = PgInterval::try_from(Duration::minutes(interval as i64))
-            .map_err(|error| anyhow::anyhow!(error))?;
-        sqlx::query(
-            "SELECT to_char(time, 'HH24:MI') FROM get_relevant_time($1,$2,$3,$4) as time WHERE time BETWEEN $5 AND $6",
-        )
-        .bind(date)
-        .bind(time)
-        .bind(max_enrollee as i32)
-        .bind(interval)
-        .bind(first_time)
-        .bind(second_time)
-        .fetch_all(&self.pool)
-        .await
-        .map_err(|error| anyhow::anyhow!(error))
-        .map(|intervals| intervals.iter().map(|row| row.get(0)).collect())
+    pub async fn get_days(&self, from: NaiveDate) -> Result<Vec<NaiveDate>> {
+        schedule::get_days(&self.pool, from).await
     }
 
-    pub async fn check_time(&self, date: NaiveDate, time: NaiveTime) -> Result<bool> {
-        sqlx::query("SELECT exists(SELECT 1 FROM queue WHERE date = $1 AND time = $2)")
-            .bind(date)
-            .bind(time)
-            .fetch_one(&self.pool)
-            .await
-            .map_err(|error| anyhow::anyhow!(error))
-            .map(|row| row.get(0))
+    pub async fn import_schedule_day(&self, day: &ScheduleDay) -> Result<bool> {
+        schedule::insert(&mut *self.pool.acquire().await?, day).await
+    }
+
+    pub async fn get_relevant_time(
+        &self,
+        date: NaiveDate,
+        schedule: &Schedule,
+    ) -> Result<Vec<NaiveTime>> {
+        queue::get_relevant_time(&self.pool, date, schedule).await
     }
 
     pub async fn register_in_queue(
@@ -150,15 +96,32 @@ impl Database<Json> {
         id: i64,
         date: NaiveDate,
         time: NaiveTime,
-    ) -> Result<bool> {
-        sqlx::query("SELECT * FROM register_in_queue($1,$2,$3)")
-            .bind(id)
-            .bind(date)
-            .bind(time)
-            .fetch_one(&self.pool)
-            .await
-            .map_err(|error| anyhow::anyhow!(error))
-            .map(|row| row.get(0))
+    ) -> Result<bool, BookingError> {
+        let mut connection = self.pool.acquire().await?;
+        let enrollee = enrollee::get_id_by_chat_id(&mut connection, id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Failed to find enrollee with chat id: {}", id))?;
+        queue::register_in_queue(&mut connection, enrollee, date, time).await
+    }
+
+    pub async fn get_enrollee_id(&self, id: i64) -> Result<Option<i32>> {
+        enrollee::get_id_by_chat_id(&self.pool, id).await
+    }
+
+    pub async fn get_language(&self, id: i64) -> Result<Option<Language>> {
+        enrollee::get_language(&self.pool, id).await
+    }
+
+    pub async fn set_language(&self, id: i64, language: Language) -> Result<bool> {
+        enrollee::set_language(&self.pool, id, language).await
+    }
+
+    pub async fn get_booking(&self, enrollee: i32) -> Result<Option<Booking>> {
+        queue::get_booking(&self.pool, enrollee).await
+    }
+
+    pub async fn cancel_booking(&self, enrollee: i32) -> Result<bool> {
+        queue::cancel(&self.pool, enrollee).await
     }
 
     pub async fn toggle_notification(&self, id: i64) -> Result<bool> {
@@ -170,6 +133,46 @@ impl Database<Json> {
             .map(|row| row.get(0))
     }
 
+    /// Waiting bookings with a slot in `from..to` that did not get the `kind` reminder yet.
+    pub async fn get_due_reminders(
+        &self,
+        kind: &str,
+        from: NaiveDateTime,
+        to: NaiveDateTime,
+    ) -> Result<Vec<DueReminder>> {
+        sqlx::query_as(
+            "SELECT enrollee.id AS enrollee, chat_id, date, time
+                FROM queue JOIN enrollee ON enrollee.id = queue.enrollee
+                WHERE status = 'wait' AND chat_id IS NOT NULL AND NOT banned
+                    AND date + time >= $1 AND date + time < $2
+                    AND NOT EXISTS (
+                        SELECT 1 FROM sent_reminder WHERE sent_reminder.enrollee = queue.enrollee
+                            AND kind = $3 AND sent_reminder.date = queue.date
+                            AND sent_reminder.time = queue.time
+                    )",
+        )
+        .bind(from)
+        .bind(to)
+        .bind(kind)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|error| anyhow::anyhow!(error))
+    }
+
+    pub async fn mark_reminder_sent(&self, kind: &str, reminder: &DueReminder) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO sent_reminder (enrollee, kind, date, time) VALUES ($1, $2, $3, $4)
+                ON CONFLICT DO NOTHING",
+        )
+        .bind(reminder.enrollee)
+        .bind(kind)
+        .bind(reminder.date)
+        .bind(reminder.time)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
     //TODO cascade delete
     pub async fn refresh_user_state(&self, id: i64) -> Result<()> {
         let id: Option<i32> = sqlx::query("SELECT id FROM enrollee WHERE chat_id = $1")
@@ -191,6 +194,18 @@ impl Database<Json> {
     }
 }
 
+/// A stored dialogue that can't be deserialized anymore, e.g. one of a removed state.
+#[derive(Debug)]
+pub struct DialogueFormatError(String);
+
+impl Display for DialogueFormatError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "dialogue serialization error: {}", self.0)
+    }
+}
+
+impl std::error::Error for DialogueFormatError {}
+
 impl<S, D> Storage<D> for &'static Database<S>
 where
     S: Send + Sync + Serializer<D> + 'static,
@@ -206,13 +221,15 @@ where
         Box::pin(async move {
             Ok(match get_dialogue(&self.pool, chat_id).await? {
                 Some(d) => {
-                    let prev_dialogue = self.serializer.deserialize(&d).map_err(|error| {
-                        anyhow::anyhow!("dialogue serialization error: {}", error)
-                    })?;
+                    // Removed first, so a dialogue that can't be deserialized anymore gets replaced
                     sqlx::query("DELETE FROM teloxide_dialogues WHERE chat_id = $1")
                         .bind(chat_id)
                         .execute(&self.pool)
                         .await?;
+                    let prev_dialogue = self
+                        .serializer
+                        .deserialize(&d)
+                        .map_err(|error| DialogueFormatError(error.to_string()))?;
                     Some(prev_dialogue)
                 }
                 _ => None,
@@ -1,10 +1,13 @@
 use anyhow::Result;
 use futures::TryStreamExt;
+use queue_core::tr;
 use serde_json::Value;
 use sqlx::postgres::PgListener;
 use teloxide::prelude::{AutoSend, Requester};
 use teloxide::Bot;
 
+use crate::language;
+
 pub struct Notifier {
     listener: PgListener,
     bot: AutoSend<Bot>,
@@ -23,24 +26,27 @@ impl Notifier {
         let mut stream = self.listener.into_stream();
         while let Some(notification) = stream.try_next().await? {
             let payload: Value = serde_json::from_str(notification.payload())?;
-            if let Some(id) = payload.get("f1").map(|id| id.as_i64()).flatten() {
-                if let Some(count) = payload.get("f2").map(|count| count.as_i64()).flatten() {
+            if let Some(id) = payload.get("f1").and_then(|id| id.as_i64()) {
+                if let Some(count) = payload.get("f2").and_then(|count| count.as_i64()) {
+                    let language = language::of_chat(id).await;
                     let message = match count {
                         0 => {
-                            if let Some(number) =
-                                payload.get("f3").map(|number| number.as_i64()).flatten()
-                            {
-                                format!(
-                                    "Підійшла ваша черга, ви маєте порядковий номер: {}!",
-                                    number
-                                )
-                            } else {
-                                format!("Підійшла ваша черга!")
+                            let desk = payload.get("f4").and_then(|desk| desk.as_i64());
+                            match (payload.get("f3").and_then(|number| number.as_i64()), desk) {
+                                (Some(number), Some(desk)) => tr!(
+                                    language,
+                                    "your-turn-number-desk",
+                                    number = number,
+                                    desk = desk
+                                ),
+                                (Some(number), None) => {
+                                    tr!(language, "your-turn-number", number = number)
+                                }
+                                (None, Some(desk)) => tr!(language, "your-turn-desk", desk = desk),
+                                (None, None) => tr!(language, "your-turn"),
                             }
                         }
-                        count => {
-                            format!("Перед вами в черзі перебуває {} людина(-и, -ей)", count)
-                        }
+                        count => tr!(language, "people-ahead", count = count),
                     };
                     if let Err(error) = self.bot.send_message(id, message).await {
                         log::error!("Failed to send notification message: {}", error);
@@ -0,0 +1,99 @@
+use anyhow::Result;
+use chrono::{Duration, Local, NaiveDate, NaiveTime};
+use queue_core::tr;
+use sqlx::FromRow;
+use teloxide::prelude::{AutoSend, Requester};
+use teloxide::Bot;
+
+use crate::config::Reminders;
+use crate::database::Database;
+use crate::language;
+
+const EVENING: &str = "evening";
+
+#[derive(FromRow)]
+pub struct DueReminder {
+    pub enrollee: i32,
+    pub chat_id: i64,
+    pub date: NaiveDate,
+    pub time: NaiveTime,
+}
+
+/// Sends reminders about upcoming visits. Sent reminders are stored in the
+/// `sent_reminder` table, so a restart neither repeats nor skips them.
+pub struct Reminder {
+    bot: AutoSend<Bot>,
+    config: Reminders,
+}
+
+impl Reminder {
+    pub fn new(bot: AutoSend<Bot>, config: Reminders) -> Self {
+        Reminder { bot, config }
+    }
+
+    pub async fn run(self) -> Result<()> {
+        let mut interval =
+            tokio::time::interval(std::time::Duration::from_secs(self.config.check_interval));
+        loop {
+            interval.tick().await;
+            if let Err(error) = self.send_due().await {
+                log::error!("Failed to send reminders: {}", error);
+            }
+        }
+    }
+
+    async fn send_due(&self) -> Result<()> {
+        let now = Local::now().naive_local();
+        if self.config.evening && now.time() >= self.config.evening_time {
+            let tomorrow = now.date() + Duration::days(1);
+            let from = tomorrow.and_hms_opt(0, 0, 0).unwrap();
+            for reminder in Database::global()
+                .get_due_reminders(EVENING, from, from + Duration::days(1))
+                .await?
+            {
+                let text = tr!(
+                    language::of_chat(reminder.chat_id).await,
+                    "reminder-evening",
+                    date = reminder.date.format("%d.%m.%Y").to_string(),
+                    time = reminder.time.format("%H:%M").to_string()
+                );
+                self.send(EVENING, &reminder, text).await?;
+            }
+        }
+        for minutes in &self.config.minutes_before {
+            let kind = format!("before:{}", minutes);
+            for reminder in Database::global()
+                .get_due_reminders(&kind, now, now + Duration::minutes(*minutes as i64))
+                .await?
+            {
+                let left = (reminder.date.and_time(reminder.time) - now).num_minutes();
+                let day = match (reminder.date - now.date()).num_days() {
+                    0 => "today",
+                    1 => "tomorrow",
+                    _ => "other",
+                };
+                let text = tr!(
+                    language::of_chat(reminder.chat_id).await,
+                    "reminder-before",
+                    day = day,
+                    date = reminder.date.format("%d.%m.%Y").to_string(),
+                    time = reminder.time.format("%H:%M").to_string(),
+                    minutes = left
+                );
+                self.send(&kind, &reminder, text).await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn send(&self, kind: &str, reminder: &DueReminder, text: String) -> Result<()> {
+        // Not marked on failure, so it is retried until the reminder window ends
+        match self.bot.send_message(reminder.chat_id, text).await {
+            Ok(_) => Database::global().mark_reminder_sent(kind, reminder).await,
+            Err(error) => {
+                log::error!("Failed to send reminder: {}", error);
+                Ok(())
+            }
+        }
+    }
+}
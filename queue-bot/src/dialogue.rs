@@ -2,8 +2,8 @@ use serde::{Deserialize, Serialize};
 use teloxide::macros::Transition;
 
 use crate::dialogue::states::{
-    BannedState, ReceiveCaptchaState, ReceiveDayState, ReceiveFullNameState, ReceiveIntervalState,
-    ReceivePhoneState, ReceiveTimeState, StartState,
+    BannedState, ReceiveCaptchaState, ReceiveDayState, ReceiveFullNameState, ReceivePhoneState,
+    StartState,
 };
 
 pub mod states;
@@ -16,16 +16,11 @@ pub enum Dialogue {
     ReceiveFullName(ReceiveFullNameState),
     ReceivePhone(ReceivePhoneState),
     ReceiveDay(ReceiveDayState),
-    ReceiveInterval(ReceiveIntervalState),
-    ReceiveTime(ReceiveTimeState),
 }
 
 impl Dialogue {
     pub fn is_start(&self) -> bool {
-        match &self {
-            Dialogue::Start(_) => true,
-            _ => false,
-        }
+        matches!(self, Dialogue::Start(_))
     }
 }
 
@@ -2,16 +2,12 @@ pub use banned::BannedState;
 pub use receive_captcha::ReceiveCaptchaState;
 pub use receive_day::ReceiveDayState;
 pub use receive_full_name::ReceiveFullNameState;
-pub use receive_interval::ReceiveIntervalState;
 pub use receive_phone::ReceivePhoneState;
-pub use receive_time::ReceiveTimeState;
 pub use start::StartState;
 
 mod banned;
 mod receive_captcha;
 mod receive_day;
 mod receive_full_name;
-mod receive_interval;
 mod receive_phone;
-mod receive_time;
 mod start;
@@ -1,7 +1,9 @@
+use queue_core::tr;
 use serde::{Deserialize, Serialize};
 use teloxide::prelude::*;
 
 use crate::dialogue::Dialogue;
+use crate::language;
 
 #[derive(Clone, Serialize, Deserialize)]
 pub struct BannedState;
@@ -12,6 +14,7 @@ async fn banned(
     cx: TransitionIn<AutoSend<Bot>>,
     _ans: String,
 ) -> TransitionOut<Dialogue> {
-    cx.answer("Ви були заблоковані, якщо вважаєте, що виникла помилка то зверніться до оператора технічної підтримки").await?;
+    let language = language::of_message(&cx).await;
+    cx.answer(tr!(language, "banned")).await?;
     next(Dialogue::Banned(state))
 }
@@ -1,9 +1,11 @@
+use queue_core::tr;
 use serde::{Deserialize, Serialize};
 use teloxide::prelude::*;
 
 use crate::captcha::Captcha;
 use crate::dialogue::states::{BannedState, ReceiveFullNameState, StartState};
 use crate::dialogue::Dialogue;
+use crate::language;
 
 #[derive(Clone, Serialize, Deserialize)]
 pub struct ReceiveCaptchaState {
@@ -27,7 +29,7 @@ impl ReceiveCaptchaState {
     }
 
     pub fn check_answer(&mut self, answer: String) -> CheckState {
-        self.attempt_count = self.attempt_count + 1;
+        self.attempt_count += 1;
         if self.answer == answer {
             CheckState::Correct
         } else {
@@ -43,7 +45,7 @@ impl ReceiveCaptchaState {
     fn check_attempt(&self) -> CheckState {
         if self.attempt_count >= 30 {
             CheckState::Block
-        } else if self.attempt_count % 10 == 0 {
+        } else if self.attempt_count.is_multiple_of(10) {
             CheckState::Update
         } else {
             CheckState::Incorrect
@@ -57,33 +59,30 @@ async fn receive_captcha(
     cx: TransitionIn<AutoSend<Bot>>,
     ans: String,
 ) -> TransitionOut<Dialogue> {
+    let language = language::of_message(&cx).await;
     match state.check_answer(ans) {
         CheckState::Correct => {
-            cx.answer("Капча вірна").await?;
-            cx.answer("Введіть своє ПІБ через пробіл. Наприклад: 'Іванов Іван Іванович'")
-                .await?;
+            cx.answer(tr!(language, "captcha-correct")).await?;
+            cx.answer(tr!(language, "enter-full-name")).await?;
             next(Dialogue::ReceiveFullName(ReceiveFullNameState))
         }
         CheckState::Incorrect => {
-            cx.answer("Капча невірна").await?;
+            cx.answer(tr!(language, "captcha-incorrect")).await?;
             next(Dialogue::ReceiveCaptcha(state))
         }
         CheckState::Update => {
-            cx.answer("Занадто велика кількість спроб, генеруємо нову капчу")
-                .await?;
+            cx.answer(tr!(language, "captcha-new")).await?;
             match Captcha::send(&cx).await {
                 Ok(answer) => next(Dialogue::ReceiveCaptcha(state.change_answer(answer))),
                 Err(error) => {
-                    cx.answer("Виникла помилка при створенні капчі")
-                        .send()
-                        .await?;
+                    cx.answer(tr!(language, "captcha-failed")).send().await?;
                     log::error!("Failed to send captcha: {}", error);
                     next(Dialogue::Start(StartState))
                 }
             }
         }
         CheckState::Block => {
-            cx.answer("Ви були заблоковані, якщо вважаєте, що виникла помилка то зверніться до оператора технічної підтримки").await?;
+            cx.answer(tr!(language, "banned")).await?;
             next(Dialogue::Banned(BannedState))
         }
     }
@@ -1,11 +1,13 @@
-use chrono::NaiveDate;
+use queue_core::tr;
 use serde::{Deserialize, Serialize};
 use teloxide::prelude::*;
 
-use crate::dialogue::states::ReceiveIntervalState;
 use crate::dialogue::Dialogue;
+use crate::language;
 use crate::queue::Queue;
 
+/// Booking itself goes through inline keyboard callbacks, see `callback.rs`.
+/// A date typed as text is still accepted and opens the intervals of that day.
 #[derive(Clone, Serialize, Deserialize)]
 pub struct ReceiveDayState;
 
@@ -15,22 +17,25 @@ async fn receive_day(
     cx: TransitionIn<AutoSend<Bot>>,
     ans: String,
 ) -> TransitionOut<Dialogue> {
-    match NaiveDate::parse_from_str(&format!("{}.2022", ans), "%d.%m.%Y") {
-        Ok(date) => match Queue::global().get_intervals_keyboard(date).await {
-            Ok(keyboard) => {
-                cx.answer("Оберіть проміжок часу")
-                    .reply_markup(keyboard)
-                    .await?;
-                next(Dialogue::ReceiveInterval(ReceiveIntervalState { date }))
-            }
-            Err(error) => {
-                cx.answer(error.to_string()).await?;
-                next(Dialogue::ReceiveDay(state))
-            }
-        },
-        Err(_) => {
-            cx.answer("Введено невірний формат дня").await?;
-            next(Dialogue::ReceiveDay(state))
+    let language = language::of_message(&cx).await;
+    let queue = Queue::global();
+    let reply = match queue.find_day(&ans, language).await {
+        Ok(date) => queue
+            .get_intervals_keyboard(date, language)
+            .await
+            .map(|keyboard| (tr!(language, "choose-interval"), keyboard)),
+        Err(error) => queue
+            .get_days_keyboard(language)
+            .await
+            .map(|keyboard| (error.to_string(), keyboard)),
+    };
+    match reply {
+        Ok((text, keyboard)) => {
+            cx.answer(text).reply_markup(keyboard).await?;
+        }
+        Err(error) => {
+            cx.answer(error.to_string()).await?;
         }
     }
+    next(Dialogue::ReceiveDay(state))
 }
@@ -1,9 +1,11 @@
+use queue_core::tr;
 use serde::{Deserialize, Serialize};
 use teloxide::prelude::*;
 
 use crate::database::Database;
 use crate::dialogue::states::ReceivePhoneState;
 use crate::dialogue::Dialogue;
+use crate::language;
 
 #[derive(Clone, Serialize, Deserialize)]
 pub struct ReceiveFullNameState;
@@ -14,10 +16,10 @@ async fn receive_full_name(
     cx: TransitionIn<AutoSend<Bot>>,
     ans: String,
 ) -> TransitionOut<Dialogue> {
+    let language = language::of_message(&cx).await;
     let mut full_name = ans.split_whitespace();
     if full_name.clone().by_ref().count() != 3usize {
-        cx.answer("Неправильно введено ПІБ, спробуйте ще раз!")
-            .await?;
+        cx.answer(tr!(language, "full-name-invalid")).await?;
         next(Dialogue::ReceiveFullName(state))
     } else {
         let last_name = full_name.next().unwrap();
@@ -34,19 +36,15 @@ async fn receive_full_name(
                         patronymic.to_string(),
                         last_name.to_string(),
                     );
-                    cx.answer("Введіть номер телефону в форматі +380XXXXXXXXX або 0XXXXXXXXX")
-                        .await?;
+                    cx.answer(tr!(language, "enter-phone")).await?;
                     next(Dialogue::ReceivePhone(receive_phone_state))
                 } else {
-                    cx.answer("Вас не вдалося знайти в списку заявок на вступ, можливо ви помилитися при введенні даних. Спробуйте ще раз.").await?;
+                    cx.answer(tr!(language, "full-name-not-found")).await?;
                     next(Dialogue::ReceiveFullName(state))
                 }
             }
             Err(error) => {
-                cx.answer(
-                    "Помилка під час перевірки валідності користувача, спробуйте ще раз ввести ПІБ",
-                )
-                .await?;
+                cx.answer(tr!(language, "full-name-check-failed")).await?;
                 log::error!("Database error while enrollee check: {}", error);
                 next(Dialogue::ReceiveFullName(state))
             }
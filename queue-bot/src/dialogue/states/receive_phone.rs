@@ -1,15 +1,17 @@
 use once_cell::sync::OnceCell;
+use queue_core::enrollee::NewEnrollee;
+use queue_core::i18n::Language;
+use queue_core::tr;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
-use serde_json::error::Category::Data;
 use teloxide::prelude::*;
 use teloxide::types::ParseMode;
 
 use crate::database::Database;
 use crate::dialogue::states::{ReceiveDayState, ReceiveFullNameState};
 use crate::dialogue::Dialogue;
+use crate::language;
 use crate::queue::Queue;
-use crate::user::Enrollee;
 
 #[derive(Clone, Serialize, Deserialize)]
 pub struct ReceivePhoneState {
@@ -36,30 +38,32 @@ async fn receive_phone(
     cx: TransitionIn<AutoSend<Bot>>,
     ans: String,
 ) -> TransitionOut<Dialogue> {
+    let language = language::of_message(&cx).await;
     let regex = PHONE_REGEX.get_or_init(|| {
         Regex::new(r"^\+?3?8?(0\d{9})$").expect("Failed to create phone number regex!")
     });
     if regex.is_match(&ans) {
         match cx.update.from() {
             Some(user) => {
-                let enrollee = Enrollee {
-                    chat_id: cx.update.chat.id,
+                let enrollee = NewEnrollee {
+                    chat_id: Some(cx.update.chat.id),
                     username: user.username.as_ref().map_or(String::new(), String::from),
                     name: state.name.clone(),
                     patronymic: state.patronymic.clone(),
                     last_name: state.last_name.clone(),
                     phone_number: ans.clone(),
+                    language: user.language_code.as_deref().and_then(Language::from_code),
                 };
                 match Database::global().register(enrollee).await {
                     Ok(id) => {
-                        cx.answer(format!(
-                            "Підсумкові дані: \n\
-                            Прізвище: {} \n\
-                            Ім'я: {} \n\
-                            По батькові: {} \n\
-                            Телефон: {} \n\
-                            <b>Порядковий номер для виклику: {}</b>",
-                            state.last_name, state.name, state.patronymic, ans, id
+                        cx.answer(tr!(
+                            language,
+                            "registration-summary",
+                            last_name = state.last_name.clone(),
+                            name = state.name.clone(),
+                            patronymic = state.patronymic.clone(),
+                            phone = ans.clone(),
+                            number = id
                         ))
                         .parse_mode(ParseMode::Html)
                         .await?;
@@ -68,28 +72,35 @@ async fn receive_phone(
                             .parse_mode(ParseMode::Html)
                             .await?;
                         let id = cx.update.chat.id;
-                        cx.requester.pin_chat_message(id, msg.id).await;
-                        cx.answer("Виберіть день тижня для запису")
-                            .reply_markup(Queue::global().get_days_keyboard())
-                            .await?;
+                        if let Err(error) = cx.requester.pin_chat_message(id, msg.id).await {
+                            log::error!("Failed to pin post message: {}", error);
+                        }
+                        match Queue::global().get_days_keyboard(language).await {
+                            Ok(keyboard) => {
+                                cx.answer(tr!(language, "choose-day"))
+                                    .reply_markup(keyboard)
+                                    .await?;
+                            }
+                            Err(error) => {
+                                cx.answer(error.to_string()).await?;
+                            }
+                        }
                         next(Dialogue::ReceiveDay(ReceiveDayState))
                     }
                     Err(error) => {
                         log::error!("Database error while register: {}", error);
-                        cx.answer("Помилка при реєстрації користувача!").await?;
+                        cx.answer(tr!(language, "registration-failed")).await?;
                         next(Dialogue::ReceiveFullName(ReceiveFullNameState))
                     }
                 }
             }
             None => {
-                cx.answer("Не вдалося отримати дані про користувача, спробуйте ще раз ввести ПІБ")
-                    .await?;
+                cx.answer(tr!(language, "user-data-failed")).await?;
                 next(Dialogue::ReceiveFullName(ReceiveFullNameState))
             }
         }
     } else {
-        cx.answer("Неправильно введено номер телефону, спробуйте ще раз!")
-            .await?;
+        cx.answer(tr!(language, "phone-invalid")).await?;
         next(Dialogue::ReceivePhone(state))
     }
 }
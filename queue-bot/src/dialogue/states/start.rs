@@ -1,3 +1,4 @@
+use queue_core::tr;
 use serde::{Deserialize, Serialize};
 use teloxide::prelude::*;
 use teloxide::types::ReplyMarkup;
@@ -5,6 +6,7 @@ use teloxide::types::ReplyMarkup;
 use crate::captcha::Captcha;
 use crate::dialogue::states::receive_captcha::ReceiveCaptchaState;
 use crate::dialogue::Dialogue;
+use crate::language;
 use crate::queue::Queue;
 
 #[derive(Clone, Serialize, Deserialize)]
@@ -16,28 +18,25 @@ async fn start(
     cx: TransitionIn<AutoSend<Bot>>,
     ans: String,
 ) -> TransitionOut<Dialogue> {
+    let language = language::of_message(&cx).await;
     if ans == "✅" {
-        cx.answer("Введіть капчу")
+        cx.answer(tr!(language, "enter-captcha"))
             .reply_markup(ReplyMarkup::kb_remove())
             .send()
             .await?;
         match Captcha::send(&cx).await {
             Ok(answer) => next(Dialogue::ReceiveCaptcha(ReceiveCaptchaState::new(answer))),
             Err(error) => {
-                cx.answer("Відбулася помилки при створенні капчи")
-                    .send()
-                    .await?;
+                cx.answer(tr!(language, "captcha-failed")).send().await?;
                 log::error!("Failed to send captcha: {}", error);
                 next(Dialogue::Start(state))
             }
         }
     } else {
-        cx.answer(
-            "Щоб продовжити роботу з ботом, погодьтеся зі збором та обробкою персональних даних у вигляді ПІБ та номеру телефону",
-        )
-        .reply_markup(Queue::global().get_agree_keyboard())
-        .send()
-        .await?;
+        cx.answer(tr!(language, "agreement"))
+            .reply_markup(Queue::global().get_agree_keyboard())
+            .send()
+            .await?;
         next(Dialogue::Start(state))
     }
 }
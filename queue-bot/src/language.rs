@@ -0,0 +1,31 @@
+use queue_core::i18n::Language;
+use teloxide::prelude::*;
+use teloxide::types::User;
+
+use crate::database::Database;
+
+/// Language chosen with `/language`, otherwise the one of the user's Telegram client.
+pub async fn of_user(chat_id: i64, user: Option<&User>) -> Language {
+    let client_language = || {
+        user.and_then(|user| user.language_code.as_deref())
+            .and_then(Language::from_code)
+            .unwrap_or_default()
+    };
+    match Database::global().get_language(chat_id).await {
+        Ok(Some(language)) => language,
+        Ok(None) => client_language(),
+        Err(error) => {
+            log::error!("Database error: {}", error);
+            client_language()
+        }
+    }
+}
+
+pub async fn of_message(cx: &UpdateWithCx<AutoSend<Bot>, Message>) -> Language {
+    of_user(cx.update.chat_id(), cx.update.from()).await
+}
+
+/// Language for chats without a message at hand, like notifications.
+pub async fn of_chat(chat_id: i64) -> Language {
+    of_user(chat_id, None).await
+}
@@ -1,37 +1,56 @@
 use anyhow::Result;
+use queue_core::migrate;
+use queue_core::tr;
+use sqlx::PgPool;
 use std::sync::Arc;
 use teloxide::prelude::*;
 use teloxide::types::MessageKind;
-use teloxide::RequestError;
+use teloxide::utils::command::BotCommand;
 
+use crate::command::Command;
 use crate::config::Config;
 use crate::database::notifier::Notifier;
-use crate::database::Database;
-use crate::dialogue::states::StartState;
+use crate::database::reminder::Reminder;
+use crate::database::{Database, DialogueFormatError};
+use crate::dialogue::states::{ReceiveDayState, StartState};
 use crate::dialogue::Dialogue;
 use crate::queue::Queue;
 
+mod callback;
 mod captcha;
+mod command;
 mod config;
 mod database;
 mod dialogue;
+mod language;
 mod queue;
-mod user;
 
 #[tokio::main]
 async fn main() {
     let config = Config::new("config.json")
         .await
         .expect("Failed to initialize config");
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    if args.first().map(String::as_str) == Some("migrate") {
+        let pool = PgPool::connect(&config.database_url)
+            .await
+            .expect("Failed to connect to database");
+        migrate::command(&pool, &args[1..])
+            .await
+            .expect("Failed to execute migrate command");
+        return;
+    }
     let bot = Bot::new(&config.token).auto_send();
     let notifier = Notifier::new(&config.database_url, bot.clone())
         .await
         .expect("Failed to initialize notifier");
+    let reminder = Reminder::new(bot.clone(), config.reminders.clone());
     config
         .initialize_data()
         .await
         .expect("Failed to initialize all global data");
-    tokio::try_join!(run(bot), notifier.run()).expect("Something get wrong with main tasks");
+    tokio::try_join!(run(bot), notifier.run(), reminder.run())
+        .expect("Something get wrong with main tasks");
 }
 type In = DialogueWithCx<AutoSend<Bot>, Message, Dialogue, anyhow::Error>;
 
@@ -39,16 +58,54 @@ async fn run(bot: AutoSend<Bot>) -> Result<()> {
     teloxide::enable_logging!();
     log::info!("Starting queue_bot...");
 
+    let bot_name = bot.get_me().await?.user.username.unwrap_or_default();
+    if let Err(error) = command::set_menu(&bot).await {
+        log::error!("Failed to set command menu: {}", error);
+    }
+
     Dispatcher::new(bot)
         .messages_handler(DialogueDispatcher::with_storage(
-            |DialogueWithCx { cx, dialogue }: In| async move {
-                let dialogue = dialogue.expect("std::convert::Infallible");
-                handle_message(cx, dialogue)
-                    .await
-                    .expect("Something wrong with the bot!")
+            move |DialogueWithCx { cx, dialogue }: In| {
+                let bot_name = bot_name.clone();
+                async move {
+                    let dialogue = match dialogue {
+                        Ok(dialogue) => dialogue,
+                        // Dialogues stored by older versions may refer to removed booking
+                        // states, those users are already registered and continue from the
+                        // day selection.
+                        Err(error) if error.is::<DialogueFormatError>() => {
+                            log::warn!("Failed to restore dialogue: {}", error);
+                            Dialogue::ReceiveDay(ReceiveDayState)
+                        }
+                        // The stored dialogue is kept, the user can retry once the database
+                        // is back.
+                        Err(error) => {
+                            log::error!("Failed to load dialogue: {}", error);
+                            let language = language::of_message(&cx).await;
+                            if let Err(error) = cx.answer(tr!(language, "command-error")).await {
+                                log::error!("Failed to send message: {}", error);
+                            }
+                            return DialogueStage::Exit;
+                        }
+                    };
+                    handle_message(cx, dialogue, &bot_name)
+                        .await
+                        .expect("Something wrong with the bot!")
+                }
             },
             Arc::new(Database::global()),
         ))
+        .callback_queries_handler(
+            |mut rx: DispatcherHandlerRx<AutoSend<Bot>, CallbackQuery>| async move {
+                while let Some(cx) = rx.recv().await {
+                    tokio::spawn(async move {
+                        if let Err(error) = callback::handle(cx).await {
+                            log::error!("Failed to handle callback query: {}", error);
+                        }
+                    });
+                }
+            },
+        )
         .dispatch()
         .await;
     Ok(())
@@ -57,22 +114,22 @@ async fn run(bot: AutoSend<Bot>) -> Result<()> {
 async fn handle_message(
     cx: UpdateWithCx<AutoSend<Bot>, Message>,
     dialogue: Dialogue,
+    bot_name: &str,
 ) -> TransitionOut<Dialogue> {
+    let language = language::of_message(&cx).await;
     match cx.update.text().map(ToOwned::to_owned) {
         None => {
             match cx.update.kind {
                 MessageKind::Pinned(_) => {}
                 _ => {
-                    cx.answer("Відправ мені текстове повідомлення").await?;
+                    cx.answer(tr!(language, "send-text")).await?;
                 }
             }
             next(dialogue)
         }
-        Some(ans) => match ans.as_str() {
-            "/start" => {
-                cx.answer(
-                    "Щоб продовжити роботу з ботом, погодьтеся зі збором та обробкою персональних даних у вигляді ПІБ та номеру телефону",
-                )
+        Some(ans) => match Command::parse(&ans, bot_name) {
+            Ok(Command::Start) => {
+                cx.answer(tr!(language, "agreement"))
                     .reply_markup(Queue::global().get_agree_keyboard())
                     .send()
                     .await?;
@@ -83,7 +140,7 @@ async fn handle_message(
                     {
                         Ok(()) => next(Dialogue::Start(StartState)),
                         Err(error) => {
-                            cx.answer("Не вдалося перезапустити бота").await?;
+                            cx.answer(tr!(language, "restart-failed")).await?;
                             log::error!("Database error: {}", error);
                             next(dialogue)
                         }
@@ -92,26 +149,38 @@ async fn handle_message(
                     next(dialogue)
                 }
             }
-            "/toggle_notification" => {
+            Ok(Command::ToggleNotification) => {
                 match Database::global()
                     .toggle_notification(cx.update.chat_id())
                     .await
                 {
                     Ok(state) => {
                         if state {
-                            cx.answer("Режим слідкування увімкнено").await?;
+                            cx.answer(tr!(language, "notification-enabled")).await?;
                         } else {
-                            cx.answer("Режим слідкування вимкнено").await?;
+                            cx.answer(tr!(language, "notification-disabled")).await?;
                         }
                     }
                     Err(error) => {
-                        cx.answer("Не вдалося налаштувати повідомлення").await?;
+                        cx.answer(tr!(language, "notification-failed")).await?;
                         log::error!("Database error: {}", error);
                     }
                 };
                 next(dialogue)
             }
-            ans => dialogue.react(cx, ans.to_string()).await,
+            Ok(Command::MyBooking) => {
+                command::my_booking(&cx).await?;
+                next(dialogue)
+            }
+            Ok(Command::Cancel) => {
+                command::cancel(&cx).await?;
+                next(dialogue)
+            }
+            Ok(Command::Language) => {
+                command::language(&cx).await?;
+                next(dialogue)
+            }
+            Err(_) => dialogue.react(cx, ans).await,
         },
     }
 }
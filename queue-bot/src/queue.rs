@@ -1,38 +1,25 @@
 use anyhow::{Context, Result};
-use chrono::{Local, NaiveDate, NaiveTime};
+use chrono::{Datelike, Local, NaiveDate, NaiveTime, Timelike};
 use once_cell::sync::OnceCell;
-use serde::Deserialize;
-use serde::Serialize;
-use std::collections::BTreeMap;
-use teloxide::types::{KeyboardButton, KeyboardMarkup};
+use queue_core::i18n::Language;
+use queue_core::schedule::Schedule;
+use queue_core::tr;
+use teloxide::types::{InlineKeyboardButton, InlineKeyboardMarkup, KeyboardButton, KeyboardMarkup};
 
-use crate::config;
+use crate::callback::Callback;
 use crate::database::Database;
 
-#[derive(Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct Schedule {
-    #[serde(with = "config::time_format")]
-    pub start_time: NaiveTime,
-    pub interval: u16,
-    pub max_enrollee: u16,
-}
-
 static INSTANCE: OnceCell<Queue> = OnceCell::new();
 
 pub struct Queue {
     agree_keyboard: KeyboardMarkup,
-    schedule: BTreeMap<NaiveDate, Schedule>,
 }
 
-pub async fn initialize(schedule: BTreeMap<NaiveDate, Schedule>) -> Result<()> {
+pub async fn initialize() -> Result<()> {
     let agree_keyboard = KeyboardMarkup::default()
         .append_row(vec![KeyboardButton::new("✅"), KeyboardButton::new("❌")])
         .resize_keyboard(true);
-    let queue = Queue {
-        agree_keyboard,
-        schedule,
-    };
+    let queue = Queue { agree_keyboard };
     INSTANCE
         .set(queue)
         .map_err(|_| anyhow::anyhow!("Failed to initialize database!"))
@@ -48,84 +35,127 @@ impl Queue {
     }
 
     //TODO time with timezone
-    pub fn get_days_keyboard(&self) -> KeyboardMarkup {
-        let current_date = Local::now().date().naive_utc();
-        Self::gen_two_columns_keyboard(
-            self.schedule
-                .keys()
-                .filter(|&date| date >= &current_date)
-                .map(|date| date.format("%d.%m").to_string()),
-        )
+    async fn get_days(&self, language: Language) -> Result<Vec<NaiveDate>> {
+        let current_date = Local::now().date_naive();
+        Database::global()
+            .get_days(current_date)
+            .await
+            .map_err(|error| {
+                log::error!("Database error: {}", error);
+                anyhow::anyhow!(tr!(language, "command-error"))
+            })
     }
 
-    //TODO filter intervals
-    pub async fn get_intervals_keyboard(&self, date: NaiveDate) -> Result<KeyboardMarkup> {
-        let schedule = self
-            .schedule
-            .get(&date)
-            .context("Зазначений день не знайдено")?;
-        match Database::global()
-            .get_intervals(
-                date,
-                schedule.start_time,
-                schedule.max_enrollee,
-                schedule.interval,
-            )
-            .await
-        {
-            Ok(intervals) => {
-                let keyboard = Self::gen_two_columns_keyboard(intervals.into_iter());
-                Ok(keyboard.append_row(vec![KeyboardButton::new("Повернутись назад 🔙")]))
+    pub async fn get_days_keyboard(&self, language: Language) -> Result<InlineKeyboardMarkup> {
+        let days = self.get_days(language).await?;
+        Ok(Self::gen_two_columns_keyboard(days.into_iter().map(
+            |date| (date.format("%d.%m").to_string(), Callback::Day(date)),
+        )))
+    }
+
+    /// Resolves a `%d.%m` button (or a full `%d.%m.%Y` date) against the upcoming schedule,
+    /// so the year comes from the schedule itself and campaigns may cross a new year.
+    pub async fn find_day(&self, text: &str, language: Language) -> Result<NaiveDate> {
+        let text = text.trim();
+        let (day, month, year) = match NaiveDate::parse_from_str(text, "%d.%m.%Y") {
+            Ok(date) => (date.day(), date.month(), Some(date.year())),
+            Err(_) => {
+                let mut parts = text.splitn(2, '.').map(|part| part.parse::<u32>().ok());
+                match (parts.next().flatten(), parts.next().flatten()) {
+                    (Some(day), Some(month)) => (day, month, None),
+                    _ => anyhow::bail!(tr!(language, "day-format-invalid")),
+                }
             }
+        };
+        self.get_days(language)
+            .await?
+            .into_iter()
+            .find(|date| {
+                date.day() == day
+                    && date.month() == month
+                    && year.is_none_or(|year| date.year() == year)
+            })
+            .with_context(|| tr!(language, "day-not-in-schedule"))
+    }
+
+    pub async fn get_schedule(&self, date: NaiveDate, language: Language) -> Result<Schedule> {
+        match Database::global().get_schedule(date).await {
+            Ok(schedule) => schedule.with_context(|| tr!(language, "day-not-found")),
             Err(error) => {
                 log::error!("Database error: {}", error);
-                Err(anyhow::anyhow!("Помилка при виконанні команди"))
+                Err(anyhow::anyhow!(tr!(language, "command-error")))
             }
         }
     }
 
-    pub async fn get_relevant_time_keyboard(
+    pub async fn get_intervals_keyboard(
         &self,
         date: NaiveDate,
-        first_time: NaiveTime,
-        second_time: NaiveTime,
-    ) -> Result<KeyboardMarkup> {
-        let schedule = self
-            .schedule
-            .get(&date)
-            .context("Зазначений день не знайдено")?;
-        match Database::global()
-            .get_intervals_between(
-                date,
-                schedule.start_time,
-                schedule.max_enrollee,
-                schedule.interval,
-                first_time,
-                second_time,
+        language: Language,
+    ) -> Result<InlineKeyboardMarkup> {
+        let mut hours = self
+            .get_relevant_time(date, language)
+            .await?
+            .iter()
+            .map(|time| time.hour())
+            .collect::<Vec<_>>();
+        hours.dedup();
+        let keyboard = Self::gen_two_columns_keyboard(hours.into_iter().map(|hour| {
+            (
+                format!("{}:00-{}:00", hour, hour + 1),
+                Callback::Interval(date, hour),
             )
+        }));
+        Ok(keyboard.append_row(vec![Self::button(tr!(language, "back"), Callback::Days)]))
+    }
+
+    pub async fn get_relevant_time_keyboard(
+        &self,
+        date: NaiveDate,
+        hour: u32,
+        language: Language,
+    ) -> Result<InlineKeyboardMarkup> {
+        let relevant_time = self.get_relevant_time(date, language).await?;
+        let keyboard = Self::gen_two_columns_keyboard(
+            relevant_time
+                .into_iter()
+                .filter(|time| time.hour() == hour)
+                .map(|time| (time.format("%H:%M").to_string(), Callback::Time(date, time))),
+        );
+        Ok(keyboard.append_row(vec![
+            Self::button(tr!(language, "back"), Callback::Day(date)),
+            Self::button(tr!(language, "other-date"), Callback::Days),
+        ]))
+    }
+
+    async fn get_relevant_time(
+        &self,
+        date: NaiveDate,
+        language: Language,
+    ) -> Result<Vec<NaiveTime>> {
+        let schedule = self.get_schedule(date, language).await?;
+        Database::global()
+            .get_relevant_time(date, &schedule)
             .await
-        {
-            Ok(intervals) => {
-                let keyboard = Self::gen_two_columns_keyboard(intervals.into_iter());
-                Ok(keyboard.append_row(vec![
-                    KeyboardButton::new("Повернутись назад 🔙"),
-                    KeyboardButton::new("Вибір іншої дати 🔙"),
-                ]))
-            }
-            Err(error) => {
+            .map_err(|error| {
                 log::error!("Database error: {}", error);
-                Err(anyhow::anyhow!("Помилка при виконанні команди"))
-            }
-        }
+                anyhow::anyhow!(tr!(language, "command-error"))
+            })
+    }
+
+    pub fn button(text: String, callback: Callback) -> InlineKeyboardButton {
+        InlineKeyboardButton::callback(text, callback.to_string())
     }
 
-    fn gen_two_columns_keyboard(buttons: impl Iterator<Item = String>) -> KeyboardMarkup {
+    fn gen_two_columns_keyboard(
+        buttons: impl Iterator<Item = (String, Callback)>,
+    ) -> InlineKeyboardMarkup {
         let keyboard = buttons
-            .map(KeyboardButton::new)
+            .map(|(text, callback)| Self::button(text, callback))
             .collect::<Vec<_>>()
             .chunks(2)
             .map(|x| x.to_vec())
             .collect::<Vec<_>>();
-        KeyboardMarkup::new(keyboard).resize_keyboard(true)
+        InlineKeyboardMarkup::new(keyboard)
     }
 }
@@ -1,8 +0,0 @@
-pub struct Enrollee {
-    pub chat_id: i64,
-    pub username: String,
-    pub name: String,
-    pub patronymic: String,
-    pub last_name: String,
-    pub phone_number: String,
-}
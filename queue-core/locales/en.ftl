@@ -0,0 +1,171 @@
+## Errors shared by the bot and the web API
+
+error-day-not-found = There is no schedule for this day
+error-past-date = This date has already passed
+error-unknown-time = This time is not in the schedule
+error-occupied = This time is already occupied
+error-enrollee-not-in-queue = Enrollee is not in the queue
+error-desk-not-found = There is no desk { $desk } on this day
+error-desk-busy = Desk { $desk } is busy
+error-all-desks-busy = All desks are busy
+error-no-desks = There must be at least one desk
+error-no-windows = Schedule must have at least one window
+error-invalid-window = Interval and max enrollee count must be greater than zero
+error-window-past-midnight = A window must end by midnight
+error-invalid-break = Break must end after it starts
+error-day-has-bookings = There are enrollees registered on this day
+error-bookings-outside-schedule = Enrollees registered on this day do not fit the new schedule
+
+## Web API
+
+error-not-auth = Not auth
+error-token-format = Token format error
+error-session-expired = This session has been expired
+error-forbidden = Forbidden
+error-captcha = Failed to verify captcha
+error-username-in-use = This username already in use
+error-account-id-not-found = Failed to find account with this id
+error-login-failed = Incorrect username or password
+error-login-throttled =
+    Too many failed attempts, try again in { $seconds ->
+        [one] { $seconds } second
+       *[other] { $seconds } seconds
+    }
+error-captcha-required = Confirm that you are not a robot to continue
+error-captcha-no-challenge = This captcha is solved on the provider's site
+error-captcha-throttled = Too many captchas requested, try again later
+error-fingerprint-missing = Failed to get fingerprint
+error-fingerprint-format = Failed to parse fingerprint as string
+error-fingerprint-mismatch = Failed to verify fingerprint
+error-own-account = This action can not be applied to your own account
+error-account-disabled = This account is disabled
+error-registration-closed = Registration is closed, ask an administrator for an account
+error-email-not-verified = Confirm your email with the link from the letter
+error-token-invalid = The link is invalid or has expired
+error-mail-disabled = Sending emails is not set up, ask an administrator
+error-mail-failed = Failed to send the email, try again later
+error-session-reused = This session was used from elsewhere, sign in again
+error-session-not-found = Failed to find this session
+
+## Bot
+
+command-error = Failed to execute the command
+send-text = Send me a text message
+agreement = To continue, please agree to the collection and processing of your personal data: full name and phone number
+restart-failed = Failed to restart the bot
+notification-enabled = Queue tracking is enabled
+notification-disabled = Queue tracking is disabled
+notification-failed = Failed to change notification settings
+banned = You have been blocked. If you think this is a mistake, please contact technical support
+
+enter-captcha = Enter the captcha
+captcha-failed = Failed to create a captcha
+captcha-correct = The captcha is correct
+captcha-incorrect = The captcha is incorrect
+captcha-new = Too many attempts, generating a new captcha
+
+enter-full-name = Enter your full name separated by spaces, for example: 'Ivanov Ivan Ivanovych'
+full-name-invalid = The full name is entered incorrectly, please try again!
+full-name-not-found = We could not find you in the list of applications, maybe there is a typo. Please try again.
+full-name-check-failed = Failed to check the enrollee, please enter your full name again
+
+enter-phone = Enter your phone number in the format +380XXXXXXXXX or 0XXXXXXXXX
+phone-invalid = The phone number is entered incorrectly, please try again!
+user-data-failed = Failed to get your user data, please enter your full name again
+registration-failed = Failed to register the user!
+registration-summary =
+    Summary:
+    Last name: { $last_name }
+    Name: { $name }
+    Patronymic: { $patronymic }
+    Phone: { $phone }
+    <b>Your call number: { $number }</b>
+
+choose-day = Choose a day for the appointment
+choose-interval = Choose a time range
+choose-time = Choose a time
+day-format-invalid = The day format is incorrect
+day-not-in-schedule = This day is not in the schedule, choose a day with the buttons
+day-not-found = The day was not found
+back = Back 🔙
+other-date = Choose another date 🔙
+change-booking = Change the appointment
+
+booking-created =
+    You have been registered in the queue for: { $date } { $time }
+    To keep track of the queue enter /toggle_notification (the same command turns it off)
+booking-moved = You have been registered in the queue for a new time: { $date } { $time } (the old appointment is cancelled)
+booking-past-date = You can no longer book this day, choose another date
+booking-occupied = Failed to book this time, it is already taken
+booking-unknown-time = The chosen time is not in the schedule
+booking-failed = Failed to register in the queue, an error occurred
+
+menu-start = Start working with the bot
+menu-my-booking = Show my appointment
+menu-cancel = Cancel the appointment
+menu-toggle-notification = Turn queue tracking on or off
+menu-language = Change the language
+
+not-registered = You are not registered yet, enter /start
+no-booking = You have no appointment in the queue
+booking-info =
+    Your appointment:
+    Date: { $date }
+    Time: { $time }
+    Status: { $status }
+    <b>Your call number: { $number }</b>
+booking-desk = Desk: { $desk }
+status-wait = waiting
+status-inside = at the desk
+status-filled = documents filled
+status-processed = processed
+status-absent = absent
+booking-cancelled = Your appointment is cancelled
+booking-cancelled-choose-day = Your appointment is cancelled. To book again, choose a day
+nothing-to-cancel = You have no appointment that can be cancelled
+cancel-failed = Failed to cancel the appointment
+
+language-name = English 🇬🇧
+choose-language = Choose a language
+language-changed = The language is changed to English
+language-failed = Failed to change the language
+
+your-turn = It is your turn!
+your-turn-number = It is your turn, your call number is { $number }!
+your-turn-desk = It is your turn! Please go to desk { $desk }
+your-turn-number-desk = It is your turn, your call number is { $number }! Please go to desk { $desk }
+people-ahead =
+    { $count ->
+        [one] There is { $count } person ahead of you in the queue
+       *[other] There are { $count } people ahead of you in the queue
+    }
+
+reminder-evening = A reminder that tomorrow ({ $date }) at { $time } you have an appointment with the admission committee. If you cannot come, cancel it with /cancel
+reminder-before =
+    A reminder that your appointment is { $day ->
+        [today] today
+        [tomorrow] tomorrow
+       *[other] on { $date }
+    } at { $time }, { $minutes ->
+        [one] { $minutes } minute
+       *[other] { $minutes } minutes
+    } left
+
+## Emails
+
+mail-verify-subject = Confirm your email
+mail-verify-body =
+    Hello!
+
+    To finish the registration, follow the link:
+    { $link }
+
+    If you did not register, just ignore this letter.
+mail-reset-subject = Password reset
+mail-reset-body =
+    Hello, { $username }!
+
+    To set a new password, follow the link:
+    { $link }
+
+    The link is valid for { $minutes } minutes. If you did not ask to reset the password, just ignore this letter.
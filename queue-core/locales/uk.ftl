@@ -0,0 +1,174 @@
+## Errors shared by the bot and the web API
+
+error-day-not-found = На цей день немає розкладу
+error-past-date = Ця дата вже минула
+error-unknown-time = Цього часу немає в розкладі
+error-occupied = Цей час вже зайнято
+error-enrollee-not-in-queue = Абітурієнта немає в черзі
+error-desk-not-found = На цей день немає столу №{ $desk }
+error-desk-busy = Стіл №{ $desk } зайнятий
+error-all-desks-busy = Усі столи зайняті
+error-no-desks = У розкладі має бути хоча б один стіл
+error-no-windows = У розкладі має бути хоча б одне вікно прийому
+error-invalid-window = Інтервал і кількість абітурієнтів мають бути більшими за нуль
+error-window-past-midnight = Вікно прийому має закінчуватися до півночі
+error-invalid-break = Перерва має закінчуватися після початку
+error-day-has-bookings = На цей день вже записані абітурієнти
+error-bookings-outside-schedule = Записи абітурієнтів на цей день не вміщуються в новий розклад
+
+## Web API
+
+error-not-auth = Потрібна авторизація
+error-token-format = Невірний формат токена
+error-session-expired = Термін дії сесії закінчився
+error-forbidden = Доступ заборонено
+error-captcha = Не вдалося перевірити капчу
+error-username-in-use = Це ім'я користувача вже зайняте
+error-account-id-not-found = Не вдалося знайти обліковий запис з таким ідентифікатором
+error-login-failed = Невірне ім'я користувача або пароль
+error-login-throttled =
+    Забагато невдалих спроб, спробуйте знову через { $seconds ->
+        [one] { $seconds } секунду
+        [few] { $seconds } секунди
+       *[other] { $seconds } секунд
+    }
+error-captcha-required = Підтвердіть, що ви не робот, щоб продовжити
+error-captcha-no-challenge = Ця капча розв'язується на сайті постачальника
+error-captcha-throttled = Забагато запитів капчі, спробуйте пізніше
+error-fingerprint-missing = Не вдалося отримати відбиток браузера
+error-fingerprint-format = Не вдалося розпізнати відбиток браузера
+error-fingerprint-mismatch = Не вдалося перевірити відбиток браузера
+error-own-account = Цю дію не можна виконати над власним обліковим записом
+error-account-disabled = Обліковий запис вимкнено
+error-registration-closed = Реєстрацію закрито, зверніться до адміністратора
+error-email-not-verified = Підтвердіть електронну пошту за посиланням з листа
+error-token-invalid = Посилання недійсне або термін його дії закінчився
+error-mail-disabled = Надсилання листів не налаштовано, зверніться до адміністратора
+error-mail-failed = Не вдалося надіслати лист, спробуйте пізніше
+error-session-reused = Сесію було використано з іншого пристрою, увійдіть знову
+error-session-not-found = Не вдалося знайти цю сесію
+
+## Bot
+
+command-error = Помилка при виконанні команди
+send-text = Відправ мені текстове повідомлення
+agreement = Щоб продовжити роботу з ботом, погодьтеся зі збором та обробкою персональних даних у вигляді ПІБ та номеру телефону
+restart-failed = Не вдалося перезапустити бота
+notification-enabled = Режим слідкування увімкнено
+notification-disabled = Режим слідкування вимкнено
+notification-failed = Не вдалося налаштувати повідомлення
+banned = Ви були заблоковані, якщо вважаєте, що виникла помилка то зверніться до оператора технічної підтримки
+
+enter-captcha = Введіть капчу
+captcha-failed = Виникла помилка при створенні капчі
+captcha-correct = Капча вірна
+captcha-incorrect = Капча невірна
+captcha-new = Занадто велика кількість спроб, генеруємо нову капчу
+
+enter-full-name = Введіть своє ПІБ через пробіл. Наприклад: 'Іванов Іван Іванович'
+full-name-invalid = Неправильно введено ПІБ, спробуйте ще раз!
+full-name-not-found = Вас не вдалося знайти в списку заявок на вступ, можливо ви помилитися при введенні даних. Спробуйте ще раз.
+full-name-check-failed = Помилка під час перевірки валідності користувача, спробуйте ще раз ввести ПІБ
+
+enter-phone = Введіть номер телефону в форматі +380XXXXXXXXX або 0XXXXXXXXX
+phone-invalid = Неправильно введено номер телефону, спробуйте ще раз!
+user-data-failed = Не вдалося отримати дані про користувача, спробуйте ще раз ввести ПІБ
+registration-failed = Помилка при реєстрації користувача!
+registration-summary =
+    Підсумкові дані:
+    Прізвище: { $last_name }
+    Ім'я: { $name }
+    По батькові: { $patronymic }
+    Телефон: { $phone }
+    <b>Порядковий номер для виклику: { $number }</b>
+
+choose-day = Виберіть день для запису
+choose-interval = Оберіть проміжок часу
+choose-time = Виберіть час
+day-format-invalid = Введено невірний формат дня
+day-not-in-schedule = Цього дня немає в розкладі, оберіть день за допомогою кнопок
+day-not-found = Зазначений день не знайдено
+back = Повернутись назад 🔙
+other-date = Вибір іншої дати 🔙
+change-booking = Змінити час запису
+
+booking-created =
+    Ви були зареєстровані в черзі на: { $date } { $time }
+    Якщо бажаєте завжди слідкувати за чергою то введіть /toggle_notification (це ж саме й для вимкнення)
+booking-moved = Ви були зареєстровані в черзі на новий час: { $date } { $time } (старий запис не актуальний)
+booking-past-date = Ви не можете більше записатися на цей день, виберіть інше число
+booking-occupied = Не вдалося записатися на даний час, його вже зайнято
+booking-unknown-time = Обраного часу немає в розкладі
+booking-failed = Не вдалося зареєструватись в черзі, виникла помилка
+
+menu-start = Почати роботу з ботом
+menu-my-booking = Переглянути свій запис
+menu-cancel = Скасувати запис
+menu-toggle-notification = Увімкнути або вимкнути режим слідкування за чергою
+menu-language = Змінити мову
+
+not-registered = Ви ще не зареєстровані, введіть /start
+no-booking = У вас немає запису в черзі
+booking-info =
+    Ваш запис:
+    Дата: { $date }
+    Час: { $time }
+    Статус: { $status }
+    <b>Порядковий номер для виклику: { $number }</b>
+booking-desk = Стіл: { $desk }
+status-wait = очікування
+status-inside = на прийомі
+status-filled = документи заповнено
+status-processed = оброблено
+status-absent = відсутній
+booking-cancelled = Ваш запис скасовано
+booking-cancelled-choose-day = Ваш запис скасовано. Щоб записатися знову, виберіть день
+nothing-to-cancel = У вас немає запису, який можна скасувати
+cancel-failed = Не вдалося скасувати запис
+
+language-name = Українська 🇺🇦
+choose-language = Оберіть мову
+language-changed = Мову змінено на українську
+language-failed = Не вдалося змінити мову
+
+your-turn = Підійшла ваша черга!
+your-turn-number = Підійшла ваша черга, ви маєте порядковий номер: { $number }!
+your-turn-desk = Підійшла ваша черга! Підійдіть до столу №{ $desk }
+your-turn-number-desk = Підійшла ваша черга, ви маєте порядковий номер: { $number }! Підійдіть до столу №{ $desk }
+people-ahead =
+    Перед вами в черзі { $count ->
+        [one] перебуває { $count } людина
+        [few] перебувають { $count } людини
+       *[other] перебуває { $count } людей
+    }
+
+reminder-evening = Нагадуємо, що завтра ({ $date }) о { $time } у вас запис до приймальної комісії. Якщо ви не зможете прийти, скасуйте запис командою /cancel
+reminder-before =
+    Нагадуємо, що ваш запис { $day ->
+        [today] сьогодні
+        [tomorrow] завтра
+       *[other] { $date }
+    } о { $time }, залишилось { $minutes ->
+        [one] { $minutes } хвилина
+        [few] { $minutes } хвилини
+       *[other] { $minutes } хвилин
+    }
+
+## Emails
+
+mail-verify-subject = Підтвердження електронної пошти
+mail-verify-body =
+    Вітаємо!
+
+    Щоб завершити реєстрацію, перейдіть за посиланням:
+    { $link }
+
+    Якщо ви не реєструвалися, просто проігноруйте цей лист.
+mail-reset-subject = Відновлення пароля
+mail-reset-body =
+    Вітаємо, { $username }!
+
+    Щоб встановити новий пароль, перейдіть за посиланням:
+    { $link }
+
+    Посилання дійсне { $minutes } хв. Якщо ви не просили відновити пароль, просто проігноруйте цей лист.
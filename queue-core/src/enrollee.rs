@@ -0,0 +1,173 @@
+use anyhow::Result;
+use chrono::{NaiveDate, NaiveTime};
+use serde::{Deserialize, Serialize};
+use sqlx::postgres::PgExecutor;
+use sqlx::{FromRow, Row, Type};
+use std::fmt;
+use std::str::FromStr;
+
+use crate::i18n::Language;
+
+#[derive(Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct Enrollee {
+    pub id: i32,
+    pub last_name: String,
+    pub name: String,
+    pub patronymic: String,
+    pub date: NaiveDate,
+    pub time: NaiveTime,
+    pub status: Status,
+    #[serde(default)]
+    pub desk: Option<i32>,
+    pub username: String,
+    pub phone_number: String,
+}
+
+pub struct NewEnrollee {
+    pub chat_id: Option<i64>,
+    pub username: String,
+    pub name: String,
+    pub patronymic: String,
+    pub last_name: String,
+    pub phone_number: String,
+    pub language: Option<Language>,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize, Type)]
+#[sqlx(type_name = "status", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    Wait,
+    Inside,
+    Filled,
+    Processed,
+    Absent,
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Status::Wait => {
+                    "wait"
+                }
+                Status::Inside => {
+                    "inside"
+                }
+                Status::Filled => {
+                    "filled"
+                }
+                Status::Processed => {
+                    "processed"
+                }
+                Status::Absent => {
+                    "absent"
+                }
+            }
+        )
+    }
+}
+
+impl FromStr for Status {
+    type Err = &'static str;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input {
+            "wait" => Ok(Status::Wait),
+            "inside" => Ok(Status::Inside),
+            "filled" => Ok(Status::Filled),
+            "processed" => Ok(Status::Processed),
+            "absent" => Ok(Status::Absent),
+            _ => Err("Failed to determine status from input"),
+        }
+    }
+}
+
+pub async fn register<'e, E: PgExecutor<'e>>(executor: E, enrollee: NewEnrollee) -> Result<i32> {
+    sqlx::query("INSERT INTO enrollee (chat_id, username, name, patronymic, last_name, phone_number, language) VALUES ($1,$2,$3,$4,$5,$6,$7) RETURNING id")
+        .bind(enrollee.chat_id)
+        .bind(enrollee.username)
+        .bind(enrollee.name)
+        .bind(enrollee.patronymic)
+        .bind(enrollee.last_name)
+        .bind(enrollee.phone_number)
+        .bind(enrollee.language.map(|language| language.code()))
+        .fetch_one(executor)
+        .await
+        .map_err(|error| anyhow::anyhow!(error))
+        .map(|row| row.get(0))
+}
+
+pub async fn get_id_by_chat_id<'e, E: PgExecutor<'e>>(
+    executor: E,
+    chat_id: i64,
+) -> Result<Option<i32>> {
+    sqlx::query("SELECT id FROM enrollee WHERE chat_id = $1")
+        .bind(chat_id)
+        .fetch_optional(executor)
+        .await
+        .map_err(|error| anyhow::anyhow!(error))
+        .map(|row| row.map(|row| row.get(0)))
+}
+
+pub async fn get_language<'e, E: PgExecutor<'e>>(
+    executor: E,
+    chat_id: i64,
+) -> Result<Option<Language>> {
+    let code: Option<String> = sqlx::query("SELECT language FROM enrollee WHERE chat_id = $1")
+        .bind(chat_id)
+        .fetch_optional(executor)
+        .await?
+        .and_then(|row| row.get(0));
+    Ok(code.and_then(|code| Language::from_code(&code)))
+}
+
+/// Returns `false` if there is no enrollee with this chat id.
+pub async fn set_language<'e, E: PgExecutor<'e>>(
+    executor: E,
+    chat_id: i64,
+    language: Language,
+) -> Result<bool> {
+    sqlx::query("UPDATE enrollee SET language = $1 WHERE chat_id = $2")
+        .bind(language.code())
+        .bind(chat_id)
+        .execute(executor)
+        .await
+        .map_err(|error| anyhow::anyhow!(error))
+        .map(|result| result.rows_affected() > 0)
+}
+
+pub async fn is_enrollee_valid<'e, E: PgExecutor<'e>>(
+    executor: E,
+    last_name: &str,
+    name: &str,
+    patronymic: &str,
+) -> Result<bool> {
+    sqlx::query("SELECT * FROM is_enrollee_valid($1, $2, $3)")
+        .bind(last_name)
+        .bind(name)
+        .bind(patronymic)
+        .fetch_one(executor)
+        .await
+        .map_err(|error| anyhow::anyhow!(error))
+        .map(|row| row.get(0))
+}
+
+pub async fn update<'e, E: PgExecutor<'e>>(executor: E, enrollee: &Enrollee) -> Result<()> {
+    sqlx::query(
+        "UPDATE enrollee SET last_name = $1, name = $2, patronymic = $3, username = $4, phone_number = $5
+            WHERE id = $6"
+    )
+        .bind(&enrollee.last_name)
+        .bind(&enrollee.name)
+        .bind(&enrollee.patronymic)
+        .bind(&enrollee.username)
+        .bind(&enrollee.phone_number)
+        .bind(enrollee.id)
+        .execute(executor)
+        .await?;
+    Ok(())
+}
@@ -0,0 +1,236 @@
+use fluent_bundle::concurrent::FluentBundle;
+use fluent_bundle::types::FluentNumber;
+use fluent_bundle::FluentResource;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+use unic_langid::LanguageIdentifier;
+
+pub use fluent_bundle::{FluentArgs, FluentValue};
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Language {
+    #[default]
+    Uk,
+    En,
+}
+
+impl Language {
+    pub const ALL: [Language; 2] = [Language::Uk, Language::En];
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            Language::Uk => "uk",
+            Language::En => "en",
+        }
+    }
+
+    /// Matches a language tag such as `en-US` by its primary subtag.
+    pub fn from_code(code: &str) -> Option<Language> {
+        let primary = code.split(['-', '_']).next().unwrap_or_default();
+        Language::ALL
+            .iter()
+            .copied()
+            .find(|language| primary.eq_ignore_ascii_case(language.code()))
+    }
+
+    /// Picks the supported language with the highest weight from an `Accept-Language` header.
+    pub fn from_accept_language(header: &str) -> Option<Language> {
+        let mut languages = header
+            .split(',')
+            .filter_map(|item| {
+                let mut parts = item.trim().split(";q=");
+                let language = Language::from_code(parts.next()?.trim())?;
+                let weight = parts
+                    .next()
+                    .and_then(|weight| weight.trim().parse::<f32>().ok())
+                    .unwrap_or(1.0);
+                Some((language, weight))
+            })
+            .collect::<Vec<_>>();
+        languages.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+        languages.first().map(|(language, _)| *language)
+    }
+
+    fn source(&self) -> &'static str {
+        match self {
+            Language::Uk => include_str!("../locales/uk.ftl"),
+            Language::En => include_str!("../locales/en.ftl"),
+        }
+    }
+}
+
+impl fmt::Display for Language {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.code())
+    }
+}
+
+impl FromStr for Language {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Language::from_code(s).ok_or_else(|| anyhow::anyhow!("Unsupported language: {}", s))
+    }
+}
+
+static BUNDLES: Lazy<Vec<(Language, FluentBundle<FluentResource>)>> = Lazy::new(|| {
+    Language::ALL
+        .iter()
+        .map(|language| {
+            let identifier: LanguageIdentifier = language
+                .code()
+                .parse()
+                .expect("Failed to parse language identifier");
+            let resource = FluentResource::try_new(language.source().to_string())
+                .expect("Failed to parse message catalogue");
+            let mut bundle = FluentBundle::new_concurrent(vec![identifier]);
+            bundle.set_use_isolating(false);
+            bundle
+                .add_resource(resource)
+                .expect("Failed to add message catalogue");
+            (*language, bundle)
+        })
+        .collect()
+});
+
+fn format(language: Language, id: &str, args: Option<&FluentArgs>) -> Option<String> {
+    let (_, bundle) = BUNDLES.iter().find(|(item, _)| *item == language)?;
+    let pattern = bundle.get_message(id)?.value()?;
+    let mut errors = Vec::new();
+    Some(
+        bundle
+            .format_pattern(pattern, args, &mut errors)
+            .into_owned(),
+    )
+}
+
+/// Formats the message `id`, falling back to Ukrainian and then to the id itself.
+pub fn message(language: Language, id: &str, args: Option<&FluentArgs>) -> String {
+    format(language, id, args)
+        .or_else(|| format(Language::default(), id, args))
+        .unwrap_or_else(|| id.to_string())
+}
+
+#[macro_export]
+macro_rules! tr {
+    ($language:expr, $id:expr) => {
+        $crate::i18n::message($language, $id, None)
+    };
+    ($language:expr, $id:expr, $($name:ident = $value:expr),+ $(,)?) => {{
+        let mut args = $crate::i18n::FluentArgs::new();
+        $(args.set(stringify!($name), $value);)+
+        $crate::i18n::message($language, $id, Some(&args))
+    }};
+}
+
+/// Argument of a `LocalizedError`. `FluentValue` itself is not `Sync`, numbers are kept
+/// apart from text so plural selectors still see them as numbers.
+#[derive(Debug, Clone)]
+enum Arg {
+    Number(FluentNumber),
+    Text(String),
+}
+
+/// Error with a message from the catalogue, so every frontend can show it in its own language.
+#[derive(Debug, Clone)]
+pub struct LocalizedError {
+    id: &'static str,
+    args: Vec<(&'static str, Arg)>,
+}
+
+impl LocalizedError {
+    pub fn new(id: &'static str) -> Self {
+        LocalizedError {
+            id,
+            args: Vec::new(),
+        }
+    }
+
+    pub fn arg<T: Into<FluentValue<'static>>>(mut self, name: &'static str, value: T) -> Self {
+        let value = match value.into() {
+            FluentValue::Number(number) => Arg::Number(number),
+            FluentValue::String(text) => Arg::Text(text.into_owned()),
+            // Custom values are not `Sync` and none of the errors need them.
+            _ => return self,
+        };
+        self.args.push((name, value));
+        self
+    }
+
+    pub fn localize(&self, language: Language) -> String {
+        let mut args = FluentArgs::new();
+        for (name, value) in &self.args {
+            match value {
+                Arg::Number(number) => args.set(*name, number.clone()),
+                Arg::Text(text) => args.set(*name, text.clone()),
+            }
+        }
+        message(language, self.id, Some(&args))
+    }
+}
+
+impl fmt::Display for LocalizedError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.localize(Language::En))
+    }
+}
+
+impl std::error::Error for LocalizedError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn numbers_select_ukrainian_plurals() {
+        let error = |seconds: i64| {
+            LocalizedError::new("error-login-throttled")
+                .arg("seconds", seconds)
+                .localize(Language::Uk)
+        };
+        assert!(error(1).ends_with("через 1 секунду"));
+        assert!(error(21).ends_with("через 21 секунду"));
+        assert!(error(3).ends_with("через 3 секунди"));
+        assert!(error(24).ends_with("через 24 секунди"));
+        assert!(error(5).ends_with("через 5 секунд"));
+        assert!(error(12).ends_with("через 12 секунд"));
+    }
+
+    #[test]
+    fn numbers_select_english_plurals() {
+        let error = |seconds: i64| {
+            LocalizedError::new("error-login-throttled")
+                .arg("seconds", seconds)
+                .localize(Language::En)
+        };
+        assert!(error(1).ends_with("in 1 second"));
+        assert!(error(2).ends_with("in 2 seconds"));
+    }
+
+    #[test]
+    fn text_arguments_are_kept() {
+        let error = LocalizedError::new("error-desk-busy").arg("desk", "A".to_string());
+        assert_eq!(error.localize(Language::En), "Desk A is busy");
+    }
+
+    #[test]
+    fn picks_accept_language_by_weight() {
+        assert_eq!(
+            Language::from_accept_language("en-US,en;q=0.9,uk;q=0.8"),
+            Some(Language::En)
+        );
+        assert_eq!(
+            Language::from_accept_language("en;q=0.5, uk-UA"),
+            Some(Language::Uk)
+        );
+        assert_eq!(
+            Language::from_accept_language("de-DE, fr;q=0.9, en;q=0.1"),
+            Some(Language::En)
+        );
+        assert_eq!(Language::from_accept_language("de, fr"), None);
+        assert_eq!(Language::from_accept_language(""), None);
+    }
+}
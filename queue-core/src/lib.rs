@@ -0,0 +1,5 @@
+pub mod enrollee;
+pub mod i18n;
+pub mod migrate;
+pub mod queue;
+pub mod schedule;
@@ -0,0 +1,84 @@
+use anyhow::{Context, Result};
+use sqlx::migrate::{Migrate, Migrator};
+use sqlx::PgPool;
+use std::collections::HashSet;
+
+pub static MIGRATOR: Migrator = sqlx::migrate!("../migrations");
+
+const HELP: &str = "\
+USAGE:
+  migrate up                Applies all pending migrations
+  migrate down [VERSION]    Reverts the last migration or all migrations after VERSION
+  migrate status            Prints applied and pending migrations
+";
+
+pub async fn run(pool: &PgPool) -> Result<()> {
+    MIGRATOR
+        .run(pool)
+        .await
+        .context("Failed to apply database migrations")
+}
+
+pub async fn command(pool: &PgPool, args: &[String]) -> Result<()> {
+    match args.first().map(String::as_str) {
+        Some("up") => {
+            run(pool).await?;
+            status(pool).await
+        }
+        Some("down") => {
+            let target = match args.get(1) {
+                Some(version) => version.parse().context("Failed to parse target version")?,
+                None => {
+                    let mut applied = applied_versions(pool)
+                        .await?
+                        .into_iter()
+                        .collect::<Vec<_>>();
+                    applied.sort_unstable();
+                    applied.pop().context("There are no applied migrations")?;
+                    applied.pop().unwrap_or(0)
+                }
+            };
+            MIGRATOR
+                .undo(pool, target)
+                .await
+                .context("Failed to revert database migrations")?;
+            status(pool).await
+        }
+        Some("status") => status(pool).await,
+        _ => {
+            print!("{}", HELP);
+            Ok(())
+        }
+    }
+}
+
+async fn status(pool: &PgPool) -> Result<()> {
+    let applied = applied_versions(pool).await?;
+    for migration in MIGRATOR
+        .iter()
+        .filter(|migration| !migration.migration_type.is_down_migration())
+    {
+        println!(
+            "{:04} {:<40} {}",
+            migration.version,
+            migration.description,
+            if applied.contains(&migration.version) {
+                "applied"
+            } else {
+                "pending"
+            }
+        );
+    }
+    Ok(())
+}
+
+async fn applied_versions(pool: &PgPool) -> Result<HashSet<i64>> {
+    let mut connection = pool.acquire().await?;
+    connection.ensure_migrations_table().await?;
+    Ok(connection
+        .list_applied_migrations()
+        .await?
+        .into_iter()
+        .map(|migration| migration.version)
+        .collect())
+}
@@ -0,0 +1,278 @@
+use anyhow::Result;
+use chrono::{Local, NaiveDate, NaiveTime};
+use sqlx::postgres::PgExecutor;
+use sqlx::{Connection, FromRow, PgConnection, Row};
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::enrollee::{Enrollee, Status};
+use crate::i18n::{self, Language, LocalizedError};
+use crate::schedule::{self, Schedule};
+
+#[derive(Debug)]
+pub enum BookingError {
+    DayNotFound,
+    PastDate,
+    UnknownTime,
+    Occupied,
+    Database(anyhow::Error),
+}
+
+impl BookingError {
+    pub fn localize(&self, language: Language) -> String {
+        let id = match self {
+            BookingError::DayNotFound => "error-day-not-found",
+            BookingError::PastDate => "error-past-date",
+            BookingError::UnknownTime => "error-unknown-time",
+            BookingError::Occupied => "error-occupied",
+            BookingError::Database(error) => return error.to_string(),
+        };
+        i18n::message(language, id, None)
+    }
+}
+
+impl fmt::Display for BookingError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.localize(Language::En))
+    }
+}
+
+impl std::error::Error for BookingError {}
+
+impl From<sqlx::Error> for BookingError {
+    fn from(error: sqlx::Error) -> Self {
+        match &error {
+            sqlx::Error::Database(database_error)
+                if database_error.code().as_deref() == Some("23505") =>
+            {
+                BookingError::Occupied
+            }
+            _ => BookingError::Database(error.into()),
+        }
+    }
+}
+
+impl From<anyhow::Error> for BookingError {
+    fn from(error: anyhow::Error) -> Self {
+        BookingError::Database(error)
+    }
+}
+
+#[derive(FromRow)]
+pub struct Booking {
+    pub date: NaiveDate,
+    pub time: NaiveTime,
+    pub status: Status,
+    pub desk: Option<i32>,
+}
+
+pub fn validate(date: NaiveDate, time: NaiveTime, schedule: &Schedule) -> Result<(), BookingError> {
+    if Local::now().naive_local().date() > date {
+        return Err(BookingError::PastDate);
+    }
+    if !schedule.contains(time) {
+        return Err(BookingError::UnknownTime);
+    }
+    Ok(())
+}
+
+/// Number of bookings for every booked time of the day.
+pub async fn get_booked<'e, E: PgExecutor<'e>>(
+    executor: E,
+    date: NaiveDate,
+) -> Result<HashMap<NaiveTime, i64>> {
+    sqlx::query_as("SELECT time, COUNT(*) FROM queue WHERE date = $1 GROUP BY time")
+        .bind(date)
+        .fetch_all(executor)
+        .await
+        .map_err(|error| anyhow::anyhow!(error))
+        .map(|rows| rows.into_iter().collect())
+}
+
+pub async fn get_relevant_time<'e, E: PgExecutor<'e>>(
+    executor: E,
+    date: NaiveDate,
+    schedule: &Schedule,
+) -> Result<Vec<NaiveTime>> {
+    let booked = get_booked(executor, date).await?;
+    Ok(schedule
+        .slots()
+        .into_iter()
+        .filter(|time| booked.get(time).copied().unwrap_or(0) < schedule.desks as i64)
+        .collect())
+}
+
+/// Books `time` for the enrollee or moves an existing booking there.
+/// Returns `true` if the enrollee already had a place in the queue.
+pub async fn register_in_queue(
+    connection: &mut PgConnection,
+    enrollee: i32,
+    date: NaiveDate,
+    time: NaiveTime,
+) -> Result<bool, BookingError> {
+    let mut transaction = connection.begin().await?;
+    // Bookings of the same day are serialized so a slot never gets more enrollees than desks.
+    sqlx::query("SELECT 1 FROM schedule_day WHERE date = $1 FOR UPDATE")
+        .bind(date)
+        .execute(&mut transaction)
+        .await?;
+    let schedule = schedule::get(&mut transaction, date)
+        .await?
+        .ok_or(BookingError::DayNotFound)?;
+    validate(date, time, &schedule)?;
+    let exists = sqlx::query("SELECT exists(SELECT 1 FROM queue WHERE enrollee = $1)")
+        .bind(enrollee)
+        .fetch_one(&mut transaction)
+        .await?
+        .get(0);
+    let booked: i64 =
+        sqlx::query("SELECT COUNT(*) FROM queue WHERE date = $1 AND time = $2 AND enrollee != $3")
+            .bind(date)
+            .bind(time)
+            .bind(enrollee)
+            .fetch_one(&mut transaction)
+            .await?
+            .get(0);
+    if booked >= schedule.desks as i64 {
+        return Err(BookingError::Occupied);
+    }
+    sqlx::query(
+        "INSERT INTO queue (enrollee, date, time) VALUES ($1, $2, $3)
+            ON CONFLICT (enrollee) DO UPDATE SET date = excluded.date, time = excluded.time",
+    )
+    .bind(enrollee)
+    .bind(date)
+    .bind(time)
+    .execute(&mut transaction)
+    .await?;
+    transaction.commit().await?;
+    Ok(exists)
+}
+
+pub async fn get_booking<'e, E: PgExecutor<'e>>(
+    executor: E,
+    enrollee: i32,
+) -> Result<Option<Booking>> {
+    sqlx::query_as("SELECT date, time, status, desk FROM queue WHERE enrollee = $1")
+        .bind(enrollee)
+        .fetch_optional(executor)
+        .await
+        .map_err(|error| anyhow::anyhow!(error))
+}
+
+/// Frees the slot of a waiting enrollee, the enrollee record itself is kept.
+/// Returns `false` if there was no booking that could be cancelled.
+pub async fn cancel<'e, E: PgExecutor<'e>>(executor: E, enrollee: i32) -> Result<bool> {
+    sqlx::query("DELETE FROM queue WHERE enrollee = $1 AND status = 'wait'")
+        .bind(enrollee)
+        .execute(executor)
+        .await
+        .map_err(|error| anyhow::anyhow!(error))
+        .map(|result| result.rows_affected() > 0)
+}
+
+/// Changes the status of the enrollee. Marking an enrollee as inside seats them at
+/// `desk` or at the first free desk of the day; the assigned desk is returned. Any
+/// other status frees the desk.
+pub async fn change_status(
+    connection: &mut PgConnection,
+    enrollee: i32,
+    status: Status,
+    desk: Option<i32>,
+) -> Result<Option<i32>> {
+    if status != Status::Inside {
+        sqlx::query("UPDATE queue SET status = $1, desk = NULL WHERE enrollee = $2")
+            .bind(status)
+            .bind(enrollee)
+            .execute(connection)
+            .await?;
+        return Ok(None);
+    }
+    let mut transaction = connection.begin().await?;
+    let (date, current_status, current_desk, desks): (NaiveDate, Status, Option<i32>, i32) =
+        sqlx::query_as(
+            "SELECT queue.date, queue.status, queue.desk, COALESCE(schedule_day.desks, 1)
+                FROM queue LEFT JOIN schedule_day ON schedule_day.date = queue.date
+                WHERE enrollee = $1 FOR UPDATE OF queue",
+        )
+        .bind(enrollee)
+        .fetch_optional(&mut transaction)
+        .await?
+        .ok_or_else(|| LocalizedError::new("error-enrollee-not-in-queue"))?;
+    let busy: Vec<i32> = sqlx::query(
+        "SELECT desk FROM queue
+            WHERE date = $1 AND status = 'inside' AND desk IS NOT NULL AND enrollee != $2",
+    )
+    .bind(date)
+    .bind(enrollee)
+    .fetch_all(&mut transaction)
+    .await?
+    .iter()
+    .map(|row| row.get(0))
+    .collect();
+    let desk = match desk {
+        Some(desk) => {
+            if desk < 1 || desk > desks {
+                return Err(LocalizedError::new("error-desk-not-found")
+                    .arg("desk", desk)
+                    .into());
+            }
+            if busy.contains(&desk) {
+                return Err(LocalizedError::new("error-desk-busy")
+                    .arg("desk", desk)
+                    .into());
+            }
+            desk
+        }
+        None => match current_desk {
+            Some(desk) if current_status == Status::Inside => desk,
+            _ => (1..=desks)
+                .find(|desk| !busy.contains(desk))
+                .ok_or_else(|| LocalizedError::new("error-all-desks-busy"))?,
+        },
+    };
+    sqlx::query("UPDATE queue SET status = $1, desk = $2 WHERE enrollee = $3")
+        .bind(status)
+        .bind(desk)
+        .bind(enrollee)
+        .execute(&mut transaction)
+        .await?;
+    transaction.commit().await?;
+    Ok(Some(desk))
+}
+
+pub async fn get_dates<'e, E: PgExecutor<'e>>(executor: E) -> Result<Vec<NaiveDate>> {
+    sqlx::query("SELECT DISTINCT date FROM queue ORDER BY date")
+        .fetch_all(executor)
+        .await
+        .map_err(|error| anyhow::anyhow!(error))
+        .map(|dates| dates.iter().map(|row| row.get(0)).collect())
+}
+
+pub async fn get_enrollees<'e, E: PgExecutor<'e>>(
+    executor: E,
+    dates: Vec<NaiveDate>,
+) -> Result<Vec<Enrollee>> {
+    sqlx::query_as(
+        "SELECT id, last_name, name, patronymic, date, time, status, desk, username, phone_number
+            FROM enrollee INNER JOIN queue ON enrollee.id = queue.enrollee
+            WHERE (SELECT date = ANY ($1))
+            ORDER BY date, time",
+    )
+    .bind(dates)
+    .fetch_all(executor)
+    .await
+    .map_err(|error| anyhow::anyhow!(error))
+}
+
+pub async fn get_enrollee<'e, E: PgExecutor<'e>>(executor: E, id: i32) -> Result<Option<Enrollee>> {
+    sqlx::query_as(
+        "SELECT id, last_name, name, patronymic, date, time, status, desk, username, phone_number
+            FROM enrollee INNER JOIN queue ON enrollee.id = queue.enrollee
+            WHERE id = $1",
+    )
+    .bind(id)
+    .fetch_optional(executor)
+    .await
+    .map_err(|error| anyhow::anyhow!(error))
+}
@@ -0,0 +1,317 @@
+use anyhow::Result;
+use chrono::{Duration, NaiveDate, NaiveTime, Timelike};
+use serde::{Deserialize, Serialize};
+use sqlx::postgres::{PgExecutor, PgRow};
+use sqlx::{Connection, PgConnection, Row};
+
+use crate::i18n::LocalizedError;
+use crate::queue;
+
+pub mod time_format;
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Window {
+    #[serde(with = "time_format")]
+    pub start_time: NaiveTime,
+    pub interval: u16,
+    pub max_enrollee: u16,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Break {
+    #[serde(with = "time_format")]
+    pub start_time: NaiveTime,
+    #[serde(with = "time_format")]
+    pub end_time: NaiveTime,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Schedule {
+    pub windows: Vec<Window>,
+    #[serde(default)]
+    pub breaks: Vec<Break>,
+    #[serde(default = "default_desks")]
+    pub desks: u16,
+}
+
+fn default_desks() -> u16 {
+    1
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduleDay {
+    pub date: NaiveDate,
+    #[serde(flatten)]
+    pub schedule: Schedule,
+}
+
+impl Window {
+    /// Slots stop at midnight instead of wrapping to the morning of the same day.
+    pub fn slots(&self) -> impl Iterator<Item = NaiveTime> + '_ {
+        let interval = Duration::minutes(self.interval as i64);
+        (0..self.max_enrollee as i32).map_while(move |index| {
+            match self.start_time.overflowing_add_signed(interval * index) {
+                (time, 0) => Some(time),
+                _ => None,
+            }
+        })
+    }
+
+    /// The last slot is over by midnight.
+    fn ends_by_midnight(&self) -> bool {
+        let length = self.interval as u64 * self.max_enrollee as u64 * 60;
+        self.start_time.num_seconds_from_midnight() as u64 + length <= 24 * 60 * 60
+    }
+
+    fn from_row(row: &PgRow) -> Window {
+        Window {
+            start_time: row.get("start_time"),
+            interval: row.get::<i32, _>("interval") as u16,
+            max_enrollee: row.get::<i32, _>("max_enrollee") as u16,
+        }
+    }
+}
+
+impl Break {
+    pub fn contains(&self, time: NaiveTime) -> bool {
+        self.start_time <= time && time < self.end_time
+    }
+
+    fn from_row(row: &PgRow) -> Break {
+        Break {
+            start_time: row.get("start_time"),
+            end_time: row.get("end_time"),
+        }
+    }
+}
+
+impl From<Window> for Schedule {
+    fn from(window: Window) -> Self {
+        Schedule {
+            windows: vec![window],
+            breaks: Vec::new(),
+            desks: default_desks(),
+        }
+    }
+}
+
+impl Schedule {
+    /// Sorted slots of all windows without the ones that fall inside a break.
+    pub fn slots(&self) -> Vec<NaiveTime> {
+        let mut slots: Vec<NaiveTime> = self
+            .windows
+            .iter()
+            .flat_map(Window::slots)
+            .filter(|time| !self.breaks.iter().any(|item| item.contains(*time)))
+            .collect();
+        slots.sort();
+        slots.dedup();
+        slots
+    }
+
+    pub fn contains(&self, time: NaiveTime) -> bool {
+        self.slots().contains(&time)
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if self.desks == 0 {
+            return Err(LocalizedError::new("error-no-desks").into());
+        }
+        if self.windows.is_empty() {
+            return Err(LocalizedError::new("error-no-windows").into());
+        }
+        if self
+            .windows
+            .iter()
+            .any(|window| window.interval == 0 || window.max_enrollee == 0)
+        {
+            return Err(LocalizedError::new("error-invalid-window").into());
+        }
+        if !self.windows.iter().all(Window::ends_by_midnight) {
+            return Err(LocalizedError::new("error-window-past-midnight").into());
+        }
+        if self
+            .breaks
+            .iter()
+            .any(|item| item.start_time >= item.end_time)
+        {
+            return Err(LocalizedError::new("error-invalid-break").into());
+        }
+        Ok(())
+    }
+}
+
+async fn get_windows(connection: &mut PgConnection, date: NaiveDate) -> Result<Vec<Window>> {
+    sqlx::query(
+        "SELECT start_time, interval, max_enrollee FROM schedule_window
+            WHERE date = $1 ORDER BY start_time",
+    )
+    .bind(date)
+    .fetch_all(connection)
+    .await
+    .map_err(|error| anyhow::anyhow!(error))
+    .map(|rows| rows.iter().map(Window::from_row).collect())
+}
+
+async fn get_breaks(connection: &mut PgConnection, date: NaiveDate) -> Result<Vec<Break>> {
+    sqlx::query(
+        "SELECT start_time, end_time FROM schedule_break WHERE date = $1 ORDER BY start_time",
+    )
+    .bind(date)
+    .fetch_all(connection)
+    .await
+    .map_err(|error| anyhow::anyhow!(error))
+    .map(|rows| rows.iter().map(Break::from_row).collect())
+}
+
+async fn insert_parts(connection: &mut PgConnection, day: &ScheduleDay) -> Result<()> {
+    for window in &day.schedule.windows {
+        sqlx::query(
+            "INSERT INTO schedule_window (date, start_time, interval, max_enrollee)
+                VALUES ($1, $2, $3, $4)",
+        )
+        .bind(day.date)
+        .bind(window.start_time)
+        .bind(window.interval as i32)
+        .bind(window.max_enrollee as i32)
+        .execute(&mut *connection)
+        .await?;
+    }
+    for item in &day.schedule.breaks {
+        sqlx::query("INSERT INTO schedule_break (date, start_time, end_time) VALUES ($1, $2, $3)")
+            .bind(day.date)
+            .bind(item.start_time)
+            .bind(item.end_time)
+            .execute(&mut *connection)
+            .await?;
+    }
+    Ok(())
+}
+
+pub async fn get(connection: &mut PgConnection, date: NaiveDate) -> Result<Option<Schedule>> {
+    let desks: i32 = match sqlx::query("SELECT desks FROM schedule_day WHERE date = $1")
+        .bind(date)
+        .fetch_optional(&mut *connection)
+        .await?
+    {
+        Some(row) => row.get(0),
+        None => return Ok(None),
+    };
+    Ok(Some(Schedule {
+        windows: get_windows(&mut *connection, date).await?,
+        breaks: get_breaks(&mut *connection, date).await?,
+        desks: desks as u16,
+    }))
+}
+
+pub async fn get_days<'e, E: PgExecutor<'e>>(
+    executor: E,
+    from: NaiveDate,
+) -> Result<Vec<NaiveDate>> {
+    sqlx::query("SELECT date FROM schedule_day WHERE date >= $1 ORDER BY date")
+        .bind(from)
+        .fetch_all(executor)
+        .await
+        .map_err(|error| anyhow::anyhow!(error))
+        .map(|rows| rows.iter().map(|row| row.get(0)).collect())
+}
+
+pub async fn get_all(connection: &mut PgConnection) -> Result<Vec<ScheduleDay>> {
+    let dates: Vec<(NaiveDate, i32)> =
+        sqlx::query_as("SELECT date, desks FROM schedule_day ORDER BY date")
+            .fetch_all(&mut *connection)
+            .await?;
+    let mut days = Vec::with_capacity(dates.len());
+    for (date, desks) in dates {
+        days.push(ScheduleDay {
+            date,
+            schedule: Schedule {
+                windows: get_windows(&mut *connection, date).await?,
+                breaks: get_breaks(&mut *connection, date).await?,
+                desks: desks as u16,
+            },
+        });
+    }
+    Ok(days)
+}
+
+/// Replaces all windows and breaks of the day. Fails if a booking of the day would not
+/// be a slot anymore or a slot would have more bookings than desks.
+pub async fn update(connection: &mut PgConnection, day: &ScheduleDay) -> Result<()> {
+    let mut transaction = connection.begin().await?;
+    // The upsert locks the day like `delete` does, so nobody books it until the commit.
+    sqlx::query(
+        "INSERT INTO schedule_day (date, desks) VALUES ($1, $2)
+            ON CONFLICT (date) DO UPDATE SET desks = excluded.desks",
+    )
+    .bind(day.date)
+    .bind(day.schedule.desks as i32)
+    .execute(&mut transaction)
+    .await?;
+    let slots = day.schedule.slots();
+    let orphaned = queue::get_booked(&mut transaction, day.date)
+        .await?
+        .into_iter()
+        .any(|(time, count)| !slots.contains(&time) || count > day.schedule.desks as i64);
+    if orphaned {
+        return Err(LocalizedError::new("error-bookings-outside-schedule").into());
+    }
+    sqlx::query("DELETE FROM schedule_window WHERE date = $1")
+        .bind(day.date)
+        .execute(&mut transaction)
+        .await?;
+    sqlx::query("DELETE FROM schedule_break WHERE date = $1")
+        .bind(day.date)
+        .execute(&mut transaction)
+        .await?;
+    insert_parts(&mut transaction, day).await?;
+    transaction.commit().await?;
+    Ok(())
+}
+
+/// Inserts the day only if it is not in the table yet, so existing edits are kept.
+pub async fn insert(connection: &mut PgConnection, day: &ScheduleDay) -> Result<bool> {
+    let mut transaction = connection.begin().await?;
+    let inserted = sqlx::query(
+        "INSERT INTO schedule_day (date, desks) VALUES ($1, $2) ON CONFLICT (date) DO NOTHING",
+    )
+    .bind(day.date)
+    .bind(day.schedule.desks as i32)
+    .execute(&mut transaction)
+    .await?
+    .rows_affected()
+        > 0;
+    if inserted {
+        insert_parts(&mut transaction, day).await?;
+    }
+    transaction.commit().await?;
+    Ok(inserted)
+}
+
+/// Fails if the day has bookings. The day is locked like `queue::register_in_queue`
+/// does, so nobody books it between the check and the delete.
+pub async fn delete(connection: &mut PgConnection, date: NaiveDate) -> Result<()> {
+    let mut transaction = connection.begin().await?;
+    sqlx::query("SELECT 1 FROM schedule_day WHERE date = $1 FOR UPDATE")
+        .bind(date)
+        .execute(&mut transaction)
+        .await?;
+    let booked: bool = sqlx::query("SELECT exists(SELECT 1 FROM queue WHERE date = $1)")
+        .bind(date)
+        .fetch_one(&mut transaction)
+        .await?
+        .get(0);
+    if booked {
+        return Err(LocalizedError::new("error-day-has-bookings").into());
+    }
+    sqlx::query("DELETE FROM schedule_day WHERE date = $1")
+        .bind(date)
+        .execute(&mut transaction)
+        .await?;
+    transaction.commit().await?;
+    Ok(())
+}
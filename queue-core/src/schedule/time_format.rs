@@ -1,7 +1,7 @@
 use chrono::NaiveTime;
 use serde::{Deserialize, Deserializer, Serializer};
 
-const FORMAT: &'static str = "%H:%M:%S";
+const FORMAT: &str = "%H:%M:%S";
 
 pub fn serialize<S>(time: &NaiveTime, serializer: S) -> Result<S::Ok, S::Error>
 where
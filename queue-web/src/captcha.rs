@@ -1,10 +1,55 @@
 use anyhow::{Context, Result};
+use async_trait::async_trait;
+use captcha::Difficulty;
+use chrono::{DateTime, Duration, Utc};
 use hyper::body;
-use hyper::{Body, Client, Uri};
+use hyper::{Body, Client, Method, Request};
 use hyper_tls::HttpsConnector;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use uuid::Uuid;
 use warp::Buf;
 
+/// Image for self-hosted captchas, the client answers with `{id}:{text}`.
+#[derive(Serialize)]
+pub struct Challenge {
+    pub id: Uuid,
+    /// Base64 encoded PNG.
+    pub image: String,
+}
+
+#[async_trait]
+pub trait CaptchaVerifier: Send + Sync {
+    /// Checks the token the client got after solving the captcha.
+    async fn check(&self, client_token: &str) -> Result<bool>;
+
+    /// Providers that are solved on their own site don't hand out challenges.
+    fn challenge(&self) -> Result<Option<Challenge>> {
+        Ok(None)
+    }
+}
+
+/// Both reCAPTCHA and hCaptcha answer `{"success": bool}` to a form with the secret.
+async fn siteverify(url: &str, secret: &str, client_token: &str) -> Result<bool> {
+    let form = serde_urlencoded::to_string([("secret", secret), ("response", client_token)])?;
+    let request = Request::builder()
+        .method(Method::POST)
+        .uri(url)
+        .header("content-type", "application/x-www-form-urlencoded")
+        .body(Body::from(form))?;
+    let response = Client::builder()
+        .build::<_, Body>(HttpsConnector::new())
+        .request(request)
+        .await?;
+    serde_json::from_reader::<_, Value>(body::aggregate(response).await?.reader())?
+        .get("success")
+        .context("Failed to get success field from captcha response")?
+        .as_bool()
+        .context("Failed to convert success field to bool")
+}
+
 pub struct ReCaptcha {
     token: String,
 }
@@ -13,22 +58,196 @@ impl ReCaptcha {
     pub fn new(token: String) -> ReCaptcha {
         ReCaptcha { token }
     }
-    pub async fn check(&self, client_token: &str) -> Result<bool> {
-        let uri: Uri = format!(
-            "https://www.google.com/recaptcha/api/siteverify?secret={}&response={}",
-            self.token, client_token
+}
+
+#[async_trait]
+impl CaptchaVerifier for ReCaptcha {
+    async fn check(&self, client_token: &str) -> Result<bool> {
+        siteverify(
+            "https://www.google.com/recaptcha/api/siteverify",
+            &self.token,
+            client_token,
         )
-        .parse()?;
-        let response = Client::builder()
-            .build::<_, Body>(HttpsConnector::new())
-            .get(uri)
-            .await?;
-        Ok(
-            serde_json::from_reader::<_, Value>(body::aggregate(response).await?.reader())?
-                .get("success")
-                .context("Failed to get success field from recaptcha response")?
-                .as_bool()
-                .context("Failed to convert success field to bool")?,
+        .await
+    }
+}
+
+pub struct HCaptcha {
+    secret: String,
+}
+
+impl HCaptcha {
+    pub fn new(secret: String) -> HCaptcha {
+        HCaptcha { secret }
+    }
+}
+
+#[async_trait]
+impl CaptchaVerifier for HCaptcha {
+    async fn check(&self, client_token: &str) -> Result<bool> {
+        siteverify(
+            "https://hcaptcha.com/siteverify",
+            &self.secret,
+            client_token,
         )
+        .await
+    }
+}
+
+/// Unanswered challenges kept at most, the oldest are dropped first.
+const MAX_PENDING: usize = 10_000;
+
+/// Answers by id, and the ids in the order they expire in, all challenges live equally long.
+#[derive(Default)]
+struct Pending {
+    answers: HashMap<Uuid, (String, DateTime<Utc>)>,
+    expiry: VecDeque<(Uuid, DateTime<Utc>)>,
+}
+
+/// Draws the same images as the bot, answers are kept in memory until used or expired.
+pub struct ImageCaptcha {
+    difficulty: ImageDifficulty,
+    lifetime: Duration,
+    pending: Mutex<Pending>,
+}
+
+impl ImageCaptcha {
+    pub fn new(difficulty: ImageDifficulty, lifetime: Duration) -> ImageCaptcha {
+        ImageCaptcha {
+            difficulty,
+            lifetime,
+            pending: Mutex::new(Pending::default()),
+        }
+    }
+}
+
+#[async_trait]
+impl CaptchaVerifier for ImageCaptcha {
+    async fn check(&self, client_token: &str) -> Result<bool> {
+        let (id, answer) = match client_token.split_once(':') {
+            Some((id, answer)) => (id, answer),
+            None => return Ok(false),
+        };
+        let id = match Uuid::parse_str(id) {
+            Ok(id) => id,
+            Err(_) => return Ok(false),
+        };
+        let expected = self.pending.lock().unwrap().answers.remove(&id);
+        Ok(matches!(expected, Some((expected, expires_at))
+            if expires_at > Utc::now() && expected.eq_ignore_ascii_case(answer.trim())))
+    }
+
+    fn challenge(&self) -> Result<Option<Challenge>> {
+        let captcha = captcha::gen(self.difficulty.into());
+        let answer = captcha.chars_as_string();
+        let image = captcha
+            .as_base64()
+            .context("Failed to generate captcha png")?;
+        let id = Uuid::new_v4();
+        let now = Utc::now();
+        let mut pending = self.pending.lock().unwrap();
+        // Ids of answered challenges are dropped here too, their answers are already gone
+        while let Some((oldest, expires_at)) = pending.expiry.front().copied() {
+            if expires_at > now && pending.answers.len() < MAX_PENDING {
+                break;
+            }
+            pending.expiry.pop_front();
+            pending.answers.remove(&oldest);
+        }
+        pending.answers.insert(id, (answer, now + self.lifetime));
+        pending.expiry.push_back((id, now + self.lifetime));
+        Ok(Some(Challenge { id, image }))
+    }
+}
+
+/// For tests and local runs, passes the configured token or any non-empty one.
+pub struct StubCaptcha {
+    token: Option<String>,
+}
+
+impl StubCaptcha {
+    pub fn new(token: Option<String>) -> StubCaptcha {
+        StubCaptcha { token }
+    }
+}
+
+#[async_trait]
+impl CaptchaVerifier for StubCaptcha {
+    async fn check(&self, client_token: &str) -> Result<bool> {
+        Ok(match &self.token {
+            Some(token) => token == client_token,
+            None => !client_token.is_empty(),
+        })
+    }
+}
+
+#[derive(Clone, Copy, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImageDifficulty {
+    Easy,
+    Medium,
+    Hard,
+}
+
+impl From<ImageDifficulty> for Difficulty {
+    fn from(difficulty: ImageDifficulty) -> Self {
+        match difficulty {
+            ImageDifficulty::Easy => Difficulty::Easy,
+            ImageDifficulty::Medium => Difficulty::Medium,
+            ImageDifficulty::Hard => Difficulty::Hard,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SecretConfig {
+    pub secret: String,
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImageConfig {
+    #[serde(default = "default_difficulty")]
+    pub difficulty: ImageDifficulty,
+    #[serde(default = "default_lifetime_minutes")]
+    pub lifetime_minutes: i64,
+}
+
+fn default_difficulty() -> ImageDifficulty {
+    ImageDifficulty::Easy
+}
+
+fn default_lifetime_minutes() -> i64 {
+    10
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StubConfig {
+    #[serde(default)]
+    pub token: Option<String>,
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(tag = "provider", rename_all = "lowercase")]
+pub enum CaptchaConfig {
+    Recaptcha(SecretConfig),
+    Hcaptcha(SecretConfig),
+    Image(ImageConfig),
+    Stub(StubConfig),
+}
+
+impl CaptchaConfig {
+    pub fn verifier(self) -> Box<dyn CaptchaVerifier> {
+        match self {
+            CaptchaConfig::Recaptcha(config) => Box::new(ReCaptcha::new(config.secret)),
+            CaptchaConfig::Hcaptcha(config) => Box::new(HCaptcha::new(config.secret)),
+            CaptchaConfig::Image(config) => Box::new(ImageCaptcha::new(
+                config.difficulty,
+                Duration::minutes(config.lifetime_minutes),
+            )),
+            CaptchaConfig::Stub(config) => Box::new(StubCaptcha::new(config.token)),
+        }
     }
 }
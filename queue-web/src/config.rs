@@ -2,15 +2,41 @@ use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use std::fs::OpenOptions;
 
+use crate::captcha::CaptchaConfig;
+use crate::jwt::JwtConfig;
+use crate::mail::MailConfig;
+use crate::throttle::ThrottleConfig;
+
 #[derive(Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Config {
     pub database_url: String,
     pub max_connections: u32,
     pub address: String,
+    /// reCAPTCHA secret, used when `captcha` is not set.
+    #[serde(default)]
     pub recaptcha_token: String,
+    #[serde(default)]
+    pub captcha: Option<CaptchaConfig>,
     pub public_key: String,
     pub private_key: String,
+    /// Token lifetimes, signing keys and the refresh cookie.
+    #[serde(default)]
+    pub jwt: JwtConfig,
+    /// Lets anyone create an account with `/api/user/auth/register`,
+    /// otherwise staff accounts are created by admins only.
+    #[serde(default = "default_open_registration")]
+    pub open_registration: bool,
+    /// Without it new accounts don't need to confirm the email and passwords can only
+    /// be reset by an admin.
+    #[serde(default)]
+    pub mail: Option<MailConfig>,
+    #[serde(default)]
+    pub login_throttle: ThrottleConfig,
+}
+
+fn default_open_registration() -> bool {
+    true
 }
 
 impl Config {
@@ -18,6 +44,7 @@ impl Config {
         let config_file = OpenOptions::new()
             .write(true)
             .create(true)
+            .truncate(false)
             .read(true)
             .open(config_path)?;
         match serde_json::from_reader(&config_file) {
@@ -38,8 +65,13 @@ impl Default for Config {
             max_connections: 5,
             address: "127.0.0.1:3030".to_string(),
             recaptcha_token: "".to_string(),
+            captcha: None,
             public_key: "".to_string(),
             private_key: "".to_string(),
+            jwt: JwtConfig::default(),
+            open_registration: default_open_registration(),
+            mail: None,
+            login_throttle: ThrottleConfig::default(),
         }
     }
 }
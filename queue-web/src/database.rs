@@ -1,43 +1,184 @@
 use anyhow::Result;
-use chrono::{Duration, NaiveDate, NaiveTime, Utc};
-use sqlx::postgres::{PgPoolOptions, PgRow};
+use chrono::{DateTime, Duration, NaiveDate, NaiveTime, Timelike, Utc};
+use queue_core::enrollee::{self, Enrollee, NewEnrollee, Status};
+use queue_core::i18n::LocalizedError;
+use queue_core::migrate;
+use queue_core::queue::{self, BookingError};
+use queue_core::schedule::{self, ScheduleDay};
+use serde_json::Value;
+use sqlx::postgres::{PgExecutor, PgPoolOptions, PgRow};
 use sqlx::{PgPool, Row};
+use std::collections::HashMap;
 use uuid::Uuid;
 
 use crate::handlers::admin::queue::QueueInfo;
 use crate::handlers::user::auth::RegistrationInfo;
 use crate::hash;
-use crate::model::enrollee::{Enrollee, Status};
-use crate::model::queue::{Queue, StudentsQueue};
-use crate::model::user::User;
-use sqlx::postgres::types::PgInterval;
-use std::collections::HashMap;
-use std::convert::TryFrom;
+use crate::model::audit::{Action, AuditQuery, AuditRecord};
+use crate::model::board::BoardEntry;
+use crate::model::queue::{EnrolleeApplication, Queue, StudentsQueue};
+use crate::model::user::{AuthInfo, NewUser, Role, Session, TokenKind, User, UserInfo};
+use crate::throttle::Failures;
+
+/// Signed in browsers an account may have at once.
+const MAX_SESSIONS: i64 = 5;
+
+/// Seconds a rotated refresh token still gets the current one of its family, so that
+/// tabs refreshing at once or a retried request are not taken for a stolen token.
+const REUSE_GRACE_SECONDS: f64 = 10.0;
 
 pub struct Database {
     pub pool: PgPool,
 }
 
+/// Spends the token, returns the account it was issued for if it was still valid.
+async fn take_user_token<'e, E: PgExecutor<'e>>(
+    executor: E,
+    token: Uuid,
+    kind: TokenKind,
+) -> Result<Option<i32>> {
+    Ok(sqlx::query_scalar(
+        "DELETE FROM user_token WHERE token = $1 AND kind = $2
+            RETURNING CASE WHEN expires_at > now() THEN user_id END",
+    )
+    .bind(token)
+    .bind(kind.as_str())
+    .fetch_optional(executor)
+    .await?
+    .flatten())
+}
+
+async fn audit<'e, E: PgExecutor<'e>>(
+    executor: E,
+    actor: &AuthInfo,
+    action: Action,
+    enrollee: Option<i32>,
+    before: Option<Value>,
+    after: Option<Value>,
+) -> Result<()> {
+    sqlx::query(
+        "INSERT INTO audit_log (actor_id, actor, action, enrollee, before, after)
+            VALUES ($1, $2, $3, $4, $5, $6)",
+    )
+    .bind(actor.id)
+    .bind(&actor.username)
+    .bind(action.as_str())
+    .bind(enrollee)
+    .bind(before)
+    .bind(after)
+    .execute(executor)
+    .await?;
+    Ok(())
+}
+
 impl Database {
     pub async fn new(max_connections: u32, database_url: &str) -> Result<Self> {
         let pool = PgPoolOptions::new()
             .max_connections(max_connections)
             .connect(database_url)
             .await?;
+        migrate::run(&pool).await?;
         Ok(Database { pool })
     }
 
-    pub async fn create_user(&self, info: RegistrationInfo) -> Result<()> {
+    pub async fn create_user(&self, info: RegistrationInfo, email_verified: bool) -> Result<i32> {
         let password = hash::hash_password(&info.password)?;
-        sqlx::query("INSERT INTO users (username, email, password) VALUES ($1,$2,$3)")
-            .bind(info.username)
-            .bind(info.email)
-            .bind(password)
+        Ok(sqlx::query_scalar(
+            "INSERT INTO users (username, email, password, email_verified) VALUES ($1,$2,$3,$4)
+                RETURNING id",
+        )
+        .bind(info.username)
+        .bind(info.email)
+        .bind(password)
+        .bind(email_verified)
+        .fetch_one(&self.pool)
+        .await?)
+    }
+
+    /// Removes an account that never got to be used, like one whose verification letter
+    /// couldn't be sent.
+    pub async fn remove_unverified_user(&self, user_id: i32) -> Result<()> {
+        sqlx::query("DELETE FROM users WHERE id = $1 AND NOT email_verified")
+            .bind(user_id)
             .execute(&self.pool)
             .await?;
         Ok(())
     }
 
+    pub async fn get_user_by_email(&self, email: &str) -> Result<Option<User>> {
+        Ok(
+            sqlx::query_as::<_, User>("SELECT * FROM users WHERE lower(email) = lower($1)")
+                .bind(email)
+                .fetch_optional(&self.pool)
+                .await?,
+        )
+    }
+
+    /// Issues a new token of the kind, the previous ones stop working.
+    pub async fn create_user_token(
+        &self,
+        user_id: i32,
+        kind: TokenKind,
+        lifetime: Duration,
+    ) -> Result<Uuid> {
+        let mut transaction = self.pool.begin().await?;
+        sqlx::query("DELETE FROM user_token WHERE user_id = $1 AND kind = $2")
+            .bind(user_id)
+            .bind(kind.as_str())
+            .execute(&mut transaction)
+            .await?;
+        let token = sqlx::query_scalar(
+            "INSERT INTO user_token (user_id, kind, expires_at) VALUES ($1, $2, $3)
+                RETURNING token",
+        )
+        .bind(user_id)
+        .bind(kind.as_str())
+        .bind(Utc::now() + lifetime)
+        .fetch_one(&mut transaction)
+        .await?;
+        transaction.commit().await?;
+        Ok(token)
+    }
+
+    /// Returns `false` if the token is unknown or expired.
+    pub async fn verify_email(&self, token: Uuid) -> Result<bool> {
+        let mut transaction = self.pool.begin().await?;
+        let user_id = match take_user_token(&mut transaction, token, TokenKind::VerifyEmail).await?
+        {
+            Some(user_id) => user_id,
+            None => return Ok(false),
+        };
+        sqlx::query("UPDATE users SET email_verified = true WHERE id = $1")
+            .bind(user_id)
+            .execute(&mut transaction)
+            .await?;
+        transaction.commit().await?;
+        Ok(true)
+    }
+
+    /// Sets the password and ends every session of the account. Following the link
+    /// proves the email too. Returns `false` if the token is unknown or expired.
+    pub async fn reset_password(&self, token: Uuid, password: &str) -> Result<bool> {
+        let password = hash::hash_password(password)?;
+        let mut transaction = self.pool.begin().await?;
+        let user_id =
+            match take_user_token(&mut transaction, token, TokenKind::ResetPassword).await? {
+                Some(user_id) => user_id,
+                None => return Ok(false),
+            };
+        sqlx::query("UPDATE users SET password = $1, email_verified = true WHERE id = $2")
+            .bind(password)
+            .bind(user_id)
+            .execute(&mut transaction)
+            .await?;
+        sqlx::query("DELETE FROM refresh_sessions WHERE user_id = $1")
+            .bind(user_id)
+            .execute(&mut transaction)
+            .await?;
+        transaction.commit().await?;
+        Ok(true)
+    }
+
     pub async fn user_exists(&self, username: &str) -> Result<bool> {
         Ok(
             sqlx::query("SELECT exists (SELECT 1 FROM users WHERE username = $1)")
@@ -66,170 +207,693 @@ impl Database {
         )
     }
 
-    pub async fn create_refresh_session(
+    pub async fn get_users(&self) -> Result<Vec<UserInfo>> {
+        sqlx::query_as(
+            "SELECT id, username, email, role, disabled,
+                (SELECT COUNT(*) FROM refresh_sessions
+                    WHERE user_id = users.id AND rotated_at IS NULL) AS sessions
+                FROM users ORDER BY id",
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|error| anyhow::anyhow!(error))
+    }
+
+    pub async fn create_staff_user(&self, actor: &AuthInfo, user: NewUser) -> Result<i32> {
+        let password = hash::hash_password(&user.password)?;
+        let mut transaction = self.pool.begin().await?;
+        let id: i32 = sqlx::query_scalar(
+            "INSERT INTO users (username, email, password, role, email_verified)
+                VALUES ($1, $2, $3, $4, true) RETURNING id",
+        )
+        .bind(&user.username)
+        .bind(&user.email)
+        .bind(password)
+        .bind(&user.role)
+        .fetch_one(&mut transaction)
+        .await?;
+        audit(
+            &mut transaction,
+            actor,
+            Action::CreateUser,
+            None,
+            None,
+            Some(serde_json::json!({
+                "user": id,
+                "username": user.username,
+                "email": user.email,
+                "role": user.role
+            })),
+        )
+        .await?;
+        transaction.commit().await?;
+        Ok(id)
+    }
+
+    /// A disabled account can't sign in and loses its sessions.
+    /// Returns `false` if there is no such account.
+    pub async fn set_user_disabled(
         &self,
+        actor: &AuthInfo,
         user_id: i32,
-        fingerprint: &str,
-    ) -> Result<(Uuid, i64)> {
-        let sessions_count: i64 =
-            sqlx::query("SELECT COUNT(*) FROM refresh_sessions WHERE user_id = $1")
-                .bind(user_id)
-                .fetch_one(&self.pool)
-                .await?
-                .get("count");
-
-        if sessions_count >= 5 {
-            sqlx::query("DELETE FROM refresh_sessions WHERE ctid IN(SELECT ctid FROM refresh_sessions WHERE user_id = $1 AND fingerprint = $2 ORDER BY created_at DESC LIMIT 1)")
+        disabled: bool,
+    ) -> Result<bool> {
+        let mut transaction = self.pool.begin().await?;
+        let updated = sqlx::query("UPDATE users SET disabled = $1 WHERE id = $2")
+            .bind(disabled)
+            .bind(user_id)
+            .execute(&mut transaction)
+            .await?
+            .rows_affected()
+            > 0;
+        if !updated {
+            return Ok(false);
+        }
+        if disabled {
+            sqlx::query("DELETE FROM refresh_sessions WHERE user_id = $1")
                 .bind(user_id)
-                .bind(fingerprint)
-                .execute(&self.pool)
+                .execute(&mut transaction)
                 .await?;
         }
+        let action = if disabled {
+            Action::DisableUser
+        } else {
+            Action::EnableUser
+        };
+        audit(
+            &mut transaction,
+            actor,
+            action,
+            None,
+            None,
+            Some(serde_json::json!({ "user": user_id })),
+        )
+        .await?;
+        transaction.commit().await?;
+        Ok(true)
+    }
 
-        let timestamp = (Utc::now() + Duration::weeks(2)).timestamp();
-        let refresh_token = sqlx::query(
-            "INSERT INTO refresh_sessions (user_id, fingerprint, expires_in) VALUES ($1,$2,$3) RETURNING refresh_token",
+    /// Returns `false` if there is no such account.
+    pub async fn delete_user(&self, actor: &AuthInfo, user_id: i32) -> Result<bool> {
+        let mut transaction = self.pool.begin().await?;
+        let user: Option<UserInfo> = sqlx::query_as(
+            "DELETE FROM users WHERE id = $1
+                RETURNING id, username, email, role, disabled, 0::BIGINT AS sessions",
+        )
+        .bind(user_id)
+        .fetch_optional(&mut transaction)
+        .await?;
+        let user = match user {
+            Some(user) => user,
+            None => return Ok(false),
+        };
+        audit(
+            &mut transaction,
+            actor,
+            Action::DeleteUser,
+            None,
+            Some(serde_json::to_value(user)?),
+            None,
+        )
+        .await?;
+        transaction.commit().await?;
+        Ok(true)
+    }
+
+    /// Ends every session of the account, returns how many there were.
+    pub async fn remove_user_sessions(&self, actor: &AuthInfo, user_id: i32) -> Result<u64> {
+        let mut transaction = self.pool.begin().await?;
+        let removed: i64 = sqlx::query_scalar(
+            "WITH removed AS (DELETE FROM refresh_sessions WHERE user_id = $1 RETURNING rotated_at)
+                SELECT COUNT(*) FROM removed WHERE rotated_at IS NULL",
         )
+        .bind(user_id)
+        .fetch_one(&mut transaction)
+        .await?;
+        audit(
+            &mut transaction,
+            actor,
+            Action::ForceLogout,
+            None,
+            None,
+            Some(serde_json::json!({ "user": user_id, "sessions": removed })),
+        )
+        .await?;
+        transaction.commit().await?;
+        Ok(removed as u64)
+    }
+
+    /// Sets a new password and ends every session of the account.
+    /// Returns `false` if there is no such account.
+    pub async fn reset_user_password(
+        &self,
+        actor: &AuthInfo,
+        user_id: i32,
+        password: &str,
+    ) -> Result<bool> {
+        let password = hash::hash_password(password)?;
+        let mut transaction = self.pool.begin().await?;
+        let updated = sqlx::query("UPDATE users SET password = $1 WHERE id = $2")
+            .bind(password)
             .bind(user_id)
-            .bind(fingerprint)
-            .bind(timestamp)
-            .fetch_one(&self.pool)
+            .execute(&mut transaction)
             .await?
-            .get("refresh_token");
+            .rows_affected()
+            > 0;
+        if !updated {
+            return Ok(false);
+        }
+        sqlx::query("DELETE FROM refresh_sessions WHERE user_id = $1")
+            .bind(user_id)
+            .execute(&mut transaction)
+            .await?;
+        audit(
+            &mut transaction,
+            actor,
+            Action::ResetPassword,
+            None,
+            None,
+            Some(serde_json::json!({ "user": user_id })),
+        )
+        .await?;
+        transaction.commit().await?;
+        Ok(true)
+    }
+
+    /// Returns `false` if there is no such account.
+    pub async fn change_role(&self, actor: &AuthInfo, user_id: i32, role: Role) -> Result<bool> {
+        let mut transaction = self.pool.begin().await?;
+        let before: Option<Role> =
+            sqlx::query_scalar("SELECT role FROM users WHERE id = $1 FOR UPDATE")
+                .bind(user_id)
+                .fetch_optional(&mut transaction)
+                .await?;
+        let before = match before {
+            Some(before) => before,
+            None => return Ok(false),
+        };
+        sqlx::query("UPDATE users SET role = $1 WHERE id = $2")
+            .bind(&role)
+            .bind(user_id)
+            .execute(&mut transaction)
+            .await?;
+        audit(
+            &mut transaction,
+            actor,
+            Action::ChangeRole,
+            None,
+            Some(serde_json::json!({ "user": user_id, "role": before })),
+            Some(serde_json::json!({ "user": user_id, "role": role })),
+        )
+        .await?;
+        transaction.commit().await?;
+        Ok(true)
+    }
+
+    /// Starts a new token family, a sign in from the same browser replaces its old session
+    /// and the oldest ones are ended to stay within `MAX_SESSIONS`.
+    pub async fn create_refresh_session(
+        &self,
+        user_id: i32,
+        fingerprint: &str,
+        lifetime: Duration,
+    ) -> Result<(Uuid, i64)> {
+        let mut transaction = self.pool.begin().await?;
+        sqlx::query("DELETE FROM refresh_sessions WHERE user_id = $1 AND fingerprint = $2")
+            .bind(user_id)
+            .bind(fingerprint)
+            .execute(&mut transaction)
+            .await?;
+        sqlx::query(
+            "DELETE FROM refresh_sessions WHERE family IN (
+                SELECT family FROM refresh_sessions WHERE user_id = $1 AND rotated_at IS NULL
+                    ORDER BY created_at DESC OFFSET $2)",
+        )
+        .bind(user_id)
+        .bind(MAX_SESSIONS - 1)
+        .execute(&mut transaction)
+        .await?;
+
+        let timestamp = (Utc::now() + lifetime).timestamp();
+        let refresh_token = sqlx::query_scalar(
+            "INSERT INTO refresh_sessions (user_id, fingerprint, expires_in) VALUES ($1,$2,$3) RETURNING refresh_token",
+        )
+        .bind(user_id)
+        .bind(fingerprint)
+        .bind(timestamp)
+        .fetch_one(&mut transaction)
+        .await?;
+        transaction.commit().await?;
         Ok((refresh_token, timestamp))
     }
 
+    /// Rotates the refresh token within its family. A token that was already rotated means
+    /// it leaked, so every session of the account is ended, unless it was rotated within
+    /// `REUSE_GRACE_SECONDS`; then the current token of the family is handed out again.
     pub async fn update_refresh_session(
         &self,
         fingerprint: &str,
         refresh_token: Uuid,
+        lifetime: Duration,
     ) -> Result<(Uuid, i64, i32)> {
+        let mut transaction = self.pool.begin().await?;
         let row: PgRow = sqlx::query(
-            "DELETE FROM refresh_sessions WHERE refresh_token = $1 RETURNING expires_in, fingerprint, user_id",
+            "SELECT user_id, family, fingerprint, expires_in, created_at,
+                    rotated_at IS NOT NULL AS rotated,
+                    rotated_at > now() - make_interval(secs => $2) AS recently_rotated
+                FROM refresh_sessions WHERE refresh_token = $1 FOR UPDATE",
         )
-            .bind(refresh_token)
-            .fetch_one(&self.pool)
+        .bind(refresh_token)
+        .bind(REUSE_GRACE_SECONDS)
+        .fetch_optional(&mut transaction)
+        .await?
+        .ok_or_else(|| LocalizedError::new("error-session-expired"))?;
+        let user_id: i32 = row.get("user_id");
+        let family: Uuid = row.get("family");
+
+        if row.get::<Option<bool>, _>("recently_rotated") == Some(true)
+            && fingerprint == row.get::<String, _>("fingerprint")
+        {
+            let current: Option<(Uuid, i64)> = sqlx::query_as(
+                "SELECT refresh_token, expires_in FROM refresh_sessions
+                    WHERE family = $1 AND rotated_at IS NULL",
+            )
+            .bind(family)
+            .fetch_optional(&mut transaction)
             .await?;
-        if fingerprint != &row.get::<String, _>("fingerprint") {
-            return Err(anyhow::anyhow!("Failed to verify fingerprint"));
+            transaction.commit().await?;
+            return match current {
+                Some((token, expires_in)) => Ok((token, expires_in, user_id)),
+                None => Err(LocalizedError::new("error-session-expired").into()),
+            };
         }
-
-        if Utc::now().timestamp() >= row.get("expires_in") {
-            return Err(anyhow::anyhow!("This session has been expired"));
+        if row.get("rotated") {
+            log::warn!(
+                "Refresh token reuse for user {}, ending all sessions",
+                user_id
+            );
+            sqlx::query("DELETE FROM refresh_sessions WHERE user_id = $1")
+                .bind(user_id)
+                .execute(&mut transaction)
+                .await?;
+            transaction.commit().await?;
+            return Err(LocalizedError::new("error-session-reused").into());
+        }
+        let error = if fingerprint != row.get::<String, _>("fingerprint") {
+            Some("error-fingerprint-mismatch")
+        } else if Utc::now().timestamp() >= row.get("expires_in") {
+            Some("error-session-expired")
+        } else {
+            None
+        };
+        if let Some(error) = error {
+            sqlx::query("DELETE FROM refresh_sessions WHERE family = $1")
+                .bind(family)
+                .execute(&mut transaction)
+                .await?;
+            transaction.commit().await?;
+            return Err(LocalizedError::new(error).into());
         }
-        let user_id = row.get("user_id");
-        self.create_refresh_session(user_id, fingerprint)
-            .await
-            .map(|session| (session.0, session.1, user_id))
-    }
 
-    pub async fn remove_refresh_session(&self, user_id: i32, refresh_token: Uuid) -> Result<()> {
-        sqlx::query("DELETE FROM refresh_sessions WHERE user_id = $1 and refresh_token = $2")
-            .bind(user_id)
+        sqlx::query("UPDATE refresh_sessions SET rotated_at = now() WHERE refresh_token = $1")
             .bind(refresh_token)
-            .execute(&self.pool)
+            .execute(&mut transaction)
             .await?;
-        Ok(())
+        let timestamp = (Utc::now() + lifetime).timestamp();
+        let new_token = sqlx::query_scalar(
+            "INSERT INTO refresh_sessions (user_id, fingerprint, expires_in, family, created_at)
+                VALUES ($1, $2, $3, $4, $5) RETURNING refresh_token",
+        )
+        .bind(user_id)
+        .bind(fingerprint)
+        .bind(timestamp)
+        .bind(family)
+        .bind(row.get::<DateTime<Utc>, _>("created_at"))
+        .fetch_one(&mut transaction)
+        .await?;
+        // Rotated tokens only have to outlive the family to be recognised.
+        sqlx::query("UPDATE refresh_sessions SET expires_in = $1 WHERE family = $2")
+            .bind(timestamp)
+            .bind(family)
+            .execute(&mut transaction)
+            .await?;
+        transaction.commit().await?;
+        Ok((new_token, timestamp, user_id))
     }
 
-    pub async fn get_dates(&self) -> Result<Vec<NaiveDate>> {
-        sqlx::query("SELECT DISTINCT date FROM queue ORDER BY date")
-            .fetch_all(&self.pool)
-            .await
-            .map_err(|error| anyhow::anyhow!(error))
-            .map(|dates| dates.iter().map(|row| row.get(0)).collect())
+    /// Ends the session the token belongs to.
+    pub async fn remove_refresh_session(&self, user_id: i32, refresh_token: Uuid) -> Result<()> {
+        sqlx::query(
+            "DELETE FROM refresh_sessions WHERE user_id = $1 AND family IN (
+                SELECT family FROM refresh_sessions WHERE refresh_token = $2)",
+        )
+        .bind(user_id)
+        .bind(refresh_token)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
     }
 
-    pub async fn get_enrollees(&self, dates: Vec<NaiveDate>) -> Result<Vec<Enrollee>> {
+    /// Active sessions of the account, `current` marks the one holding `refresh_token`.
+    pub async fn get_sessions(
+        &self,
+        user_id: i32,
+        refresh_token: Option<Uuid>,
+    ) -> Result<Vec<Session>> {
         sqlx::query_as(
-            "SELECT id, last_name, name, patronymic, date, time, status, username, phone_number 
-                FROM enrollee INNER JOIN queue ON enrollee.id = queue.enrollee
-                WHERE (SELECT date = ANY ($1))
-                ORDER BY date, time",
+            "SELECT family AS id, fingerprint, created_at, expires_in,
+                refresh_token IS NOT DISTINCT FROM $2 AS current
+                FROM refresh_sessions
+                WHERE user_id = $1 AND rotated_at IS NULL AND expires_in > $3
+                ORDER BY created_at DESC",
         )
-        .bind(dates)
+        .bind(user_id)
+        .bind(refresh_token)
+        .bind(Utc::now().timestamp())
         .fetch_all(&self.pool)
         .await
         .map_err(|error| anyhow::anyhow!(error))
     }
 
-    pub async fn change_status(&self, id: i64, status: Status) -> Result<()> {
-        sqlx::query("UPDATE queue SET status = $1 WHERE enrollee = $2")
-            .bind(status)
-            .bind(id)
+    /// Returns `false` if the account has no such session.
+    pub async fn revoke_session(&self, user_id: i32, family: Uuid) -> Result<bool> {
+        Ok(
+            sqlx::query("DELETE FROM refresh_sessions WHERE user_id = $1 AND family = $2")
+                .bind(user_id)
+                .bind(family)
+                .execute(&self.pool)
+                .await?
+                .rows_affected()
+                > 0,
+        )
+    }
+
+    /// Drops expired sessions and account tokens and sign in failures older than
+    /// `failures_since`, returns how many rows went away.
+    pub async fn purge_expired(&self, failures_since: DateTime<Utc>) -> Result<u64> {
+        let sessions = sqlx::query("DELETE FROM refresh_sessions WHERE expires_in <= $1")
+            .bind(Utc::now().timestamp())
+            .execute(&self.pool)
+            .await?
+            .rows_affected();
+        let tokens = sqlx::query("DELETE FROM user_token WHERE expires_at <= now()")
+            .execute(&self.pool)
+            .await?
+            .rows_affected();
+        let failures = sqlx::query("DELETE FROM login_failure WHERE last_failure <= $1")
+            .bind(failures_since)
             .execute(&self.pool)
+            .await?
+            .rows_affected();
+        Ok(sessions + tokens + failures)
+    }
+
+    /// Counts an attempt under the keys before the password is checked. The failures are
+    /// locked while `attempt` counts the attempt in them or turns it away, so parallel
+    /// requests can't all get past the limits. Only a counted attempt is written.
+    pub async fn reserve_login_attempt<T, E>(
+        &self,
+        keys: &[String],
+        attempt: impl FnOnce(&mut [Failures]) -> Result<T, E>,
+    ) -> Result<Result<T, E>> {
+        let mut transaction = self.pool.begin().await?;
+        let mut failures = Vec::with_capacity(keys.len());
+        for key in keys {
+            sqlx::query(
+                "INSERT INTO login_failure (key, failures, last_failure) VALUES ($1, 0, now())
+                    ON CONFLICT (key) DO NOTHING",
+            )
+            .bind(key)
+            .execute(&mut transaction)
             .await?;
-        Ok(())
+            failures.push(
+                sqlx::query_as::<_, Failures>(
+                    "SELECT failures, last_failure FROM login_failure WHERE key = $1 FOR UPDATE",
+                )
+                .bind(key)
+                .fetch_one(&mut transaction)
+                .await?,
+            );
+        }
+        let result = attempt(&mut failures);
+        if result.is_ok() {
+            for (key, failures) in keys.iter().zip(&failures) {
+                sqlx::query(
+                    "UPDATE login_failure SET failures = $2, last_failure = $3 WHERE key = $1",
+                )
+                .bind(key)
+                .bind(failures.failures)
+                .bind(failures.last_failure)
+                .execute(&mut transaction)
+                .await?;
+            }
+            transaction.commit().await?;
+        }
+        Ok(result)
     }
 
-    pub async fn update_enrollee(&self, enrollee: Enrollee) -> Result<()> {
+    /// Takes back an attempt that turned out to be a successful sign in.
+    pub async fn release_login_attempt(&self, key: &str) -> Result<()> {
         sqlx::query(
-            "UPDATE enrollee SET last_name = $1, name = $2, patronymic = $3, username = $4, phone_number = $5
-                WHERE id = $6"
-        )
-            .bind(enrollee.last_name)
-            .bind(enrollee.name)
-            .bind(enrollee.patronymic)
-            .bind(enrollee.username)
-            .bind(enrollee.phone_number)
-            .bind(enrollee.id)
-            .execute(&self.pool)
-            .await?;
-        sqlx::query("UPDATE queue SET date = $1, time = $2, status = $3 WHERE enrollee = $4")
-            .bind(enrollee.date)
-            .bind(enrollee.time)
-            .bind(enrollee.status)
-            .bind(enrollee.id)
+            "UPDATE login_failure SET failures = failures - 1 WHERE key = $1 AND failures > 0",
+        )
+        .bind(key)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn clear_login_failures(&self, key: &str) -> Result<()> {
+        sqlx::query("DELETE FROM login_failure WHERE key = $1")
+            .bind(key)
             .execute(&self.pool)
             .await?;
         Ok(())
     }
 
+    /// Imported applications whose name matches the enrollee like `is_enrollee_valid` does.
+    pub async fn get_applications(&self, enrollee_id: i32) -> Result<Vec<EnrolleeApplication>> {
+        sqlx::query_as(
+            "SELECT a.direction, a.priority, a.score, a.status, a.original
+                FROM applications a JOIN enrollee e
+                    ON normalize_apostrophes(a.name) = concat_ws(' ', normalize_apostrophes(
+                        e.last_name), left(e.name, 1) || '.', left(e.patronymic, 1) || '.')
+                WHERE e.id = $1
+                ORDER BY a.priority NULLS LAST, a.direction",
+        )
+        .bind(enrollee_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|error| anyhow::anyhow!(error))
+    }
+
+    pub async fn get_dates(&self) -> Result<Vec<NaiveDate>> {
+        queue::get_dates(&self.pool).await
+    }
+
+    pub async fn get_enrollees(&self, dates: Vec<NaiveDate>) -> Result<Vec<Enrollee>> {
+        queue::get_enrollees(&self.pool, dates).await
+    }
+
+    pub async fn change_status(
+        &self,
+        actor: &AuthInfo,
+        id: i32,
+        status: Status,
+        desk: Option<i32>,
+    ) -> Result<Option<i32>> {
+        let mut transaction = self.pool.begin().await?;
+        let before = queue::get_enrollee(&mut transaction, id).await?;
+        let desk = queue::change_status(&mut transaction, id, status, desk).await?;
+        let after = queue::get_enrollee(&mut transaction, id).await?;
+        audit(
+            &mut transaction,
+            actor,
+            Action::ChangeStatus,
+            Some(id),
+            before.map(serde_json::to_value).transpose()?,
+            after.map(serde_json::to_value).transpose()?,
+        )
+        .await?;
+        transaction.commit().await?;
+        Ok(desk)
+    }
+
+    pub async fn update_enrollee(&self, actor: &AuthInfo, enrollee: Enrollee) -> Result<()> {
+        let mut transaction = self.pool.begin().await?;
+        let before = queue::get_enrollee(&mut transaction, enrollee.id).await?;
+        enrollee::update(&mut transaction, &enrollee).await?;
+        let (date, time): (NaiveDate, NaiveTime) =
+            sqlx::query_as("SELECT date, time FROM queue WHERE enrollee = $1")
+                .bind(enrollee.id)
+                .fetch_one(&mut transaction)
+                .await?;
+        if date != enrollee.date || time != enrollee.time {
+            queue::register_in_queue(&mut transaction, enrollee.id, enrollee.date, enrollee.time)
+                .await?;
+        }
+        queue::change_status(
+            &mut transaction,
+            enrollee.id,
+            enrollee.status,
+            enrollee.desk,
+        )
+        .await?;
+        let after = queue::get_enrollee(&mut transaction, enrollee.id).await?;
+        audit(
+            &mut transaction,
+            actor,
+            Action::UpdateEnrollee,
+            Some(enrollee.id),
+            before.map(serde_json::to_value).transpose()?,
+            after.map(serde_json::to_value).transpose()?,
+        )
+        .await?;
+        transaction.commit().await?;
+        Ok(())
+    }
+
+    pub async fn get_board(&self, date: NaiveDate) -> Result<Vec<BoardEntry>> {
+        sqlx::query_as(
+            "SELECT enrollee AS number, time, status, desk FROM queue
+                WHERE date = $1 ORDER BY time, enrollee",
+        )
+        .bind(date)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|error| anyhow::anyhow!(error))
+    }
+
     pub async fn get_students_queue(&self) -> Result<StudentsQueue> {
         sqlx::query_as::<_, Queue>(
-            "SELECT last_name, name, patronymic, date, time, phone_number, username, status, id
+            "SELECT last_name, name, patronymic, date, time, phone_number, username, status, desk, id
                 FROM queue JOIN enrollee e on e.id = queue.enrollee",
         )
         .fetch_all(&self.pool)
         .await
         .map_err(|error| anyhow::anyhow!(error))
-        .map(|queue| StudentsQueue(queue))
+        .map(StudentsQueue)
     }
 
-    //TODO remove hardcoded args
     pub async fn get_relevant_time(&self, date: NaiveDate) -> Result<HashMap<u8, Vec<u8>>> {
-        let interval =
-            PgInterval::try_from(Duration::minutes(7)).map_err(|error| anyhow::anyhow!(error))?;
-        let rows = sqlx::query("SELECT extract(hour FROM time), extract(minutes FROM time) FROM get_relevant_time($1,$2,$3,$4) as time")
-            .bind(date)
-            .bind(NaiveTime::from_hms(9, 0, 0))
-            .bind(77i32)
-            .bind(interval)
-            .fetch_all(&self.pool)
-            .await?;
-        Ok(rows.iter().fold(HashMap::new(), |mut map, row| {
-            map.entry(row.get::<f64, _>(0) as u8)
-                .or_insert_with(|| Vec::new())
-                .push(row.get::<f64, _>(1) as u8);
+        let schedule = schedule::get(&mut *self.pool.acquire().await?, date)
+            .await?
+            .ok_or(BookingError::DayNotFound)?;
+        let relevant_time = queue::get_relevant_time(&self.pool, date, &schedule).await?;
+        Ok(relevant_time.iter().fold(HashMap::new(), |mut map, time| {
+            map.entry(time.hour() as u8)
+                .or_insert_with(Vec::new)
+                .push(time.minute() as u8);
             map
         }))
     }
 
-    pub async fn register_in_queue(&self, info: QueueInfo) -> Result<i32> {
-        let id = sqlx::query("INSERT INTO enrollee (last_name, name, patronymic, phone_number) VALUES ($1, $2, $3, $4) RETURNING id")
-            .bind(info.last_name)
-            .bind(info.name)
-            .bind(info.patronymic)
-            .bind(info.phone_number)
-            .fetch_one(&self.pool)
-            .await
-            .map(|row| row.get(0))?;
-        sqlx::query("INSERT INTO queue (enrollee, date, time) VALUES ($1, $2, $3)")
-            .bind(id)
-            .bind(NaiveDate::parse_from_str(&info.date, "%Y-%m-%d")?)
-            .bind(NaiveTime::parse_from_str(&info.time, "%H:%M")?)
-            .execute(&self.pool)
-            .await?;
+    pub async fn register_in_queue(&self, actor: &AuthInfo, info: QueueInfo) -> Result<i32> {
+        let date = NaiveDate::parse_from_str(&info.date, "%Y-%m-%d")?;
+        let time = NaiveTime::parse_from_str(&info.time, "%H:%M")?;
+        let mut transaction = self.pool.begin().await?;
+        let id = enrollee::register(
+            &mut transaction,
+            NewEnrollee {
+                chat_id: None,
+                username: String::new(),
+                name: info.name,
+                patronymic: info.patronymic,
+                last_name: info.last_name,
+                phone_number: info.phone_number,
+                language: None,
+            },
+        )
+        .await?;
+        queue::register_in_queue(&mut transaction, id, date, time).await?;
+        let after = queue::get_enrollee(&mut transaction, id).await?;
+        audit(
+            &mut transaction,
+            actor,
+            Action::RegisterEnrollee,
+            Some(id),
+            None,
+            after.map(serde_json::to_value).transpose()?,
+        )
+        .await?;
+        transaction.commit().await?;
         Ok(id)
     }
+
+    pub async fn get_schedule(&self) -> Result<Vec<ScheduleDay>> {
+        schedule::get_all(&mut *self.pool.acquire().await?).await
+    }
+
+    pub async fn update_schedule_day(&self, actor: &AuthInfo, day: ScheduleDay) -> Result<()> {
+        let mut transaction = self.pool.begin().await?;
+        let before = schedule::get(&mut transaction, day.date)
+            .await?
+            .map(|schedule| ScheduleDay {
+                date: day.date,
+                schedule,
+            });
+        schedule::update(&mut transaction, &day).await?;
+        audit(
+            &mut transaction,
+            actor,
+            Action::UpdateSchedule,
+            None,
+            before.map(serde_json::to_value).transpose()?,
+            Some(serde_json::to_value(&day)?),
+        )
+        .await?;
+        transaction.commit().await?;
+        Ok(())
+    }
+
+    pub async fn delete_schedule_day(&self, actor: &AuthInfo, date: NaiveDate) -> Result<()> {
+        let mut transaction = self.pool.begin().await?;
+        let before = schedule::get(&mut transaction, date)
+            .await?
+            .map(|schedule| ScheduleDay { date, schedule });
+        schedule::delete(&mut transaction, date).await?;
+        audit(
+            &mut transaction,
+            actor,
+            Action::DeleteSchedule,
+            None,
+            before.map(serde_json::to_value).transpose()?,
+            None,
+        )
+        .await?;
+        transaction.commit().await?;
+        Ok(())
+    }
+
+    /// Page of the audit log, newest first, with the number of matching records.
+    pub async fn get_audit_log(&self, query: &AuditQuery) -> Result<(Vec<AuditRecord>, i64)> {
+        let filter = "($1::VARCHAR IS NULL OR actor = $1)
+            AND ($2::VARCHAR IS NULL OR action = $2)
+            AND ($3::INTEGER IS NULL OR enrollee = $3)
+            AND ($4::DATE IS NULL OR created_at >= $4::DATE)
+            AND ($5::DATE IS NULL OR created_at < $5::DATE + 1)";
+        let total: i64 = sqlx::query(&format!("SELECT COUNT(*) FROM audit_log WHERE {}", filter))
+            .bind(&query.actor)
+            .bind(query.action.map(|action| action.as_str()))
+            .bind(query.enrollee)
+            .bind(query.from)
+            .bind(query.to)
+            .fetch_one(&self.pool)
+            .await?
+            .get(0);
+        let records = sqlx::query_as(&format!(
+            "SELECT * FROM audit_log WHERE {} ORDER BY created_at DESC, id DESC LIMIT $6 OFFSET $7",
+            filter
+        ))
+        .bind(&query.actor)
+        .bind(query.action.map(|action| action.as_str()))
+        .bind(query.enrollee)
+        .bind(query.from)
+        .bind(query.to)
+        .bind(query.per_page as i64)
+        .bind(query.page as i64 * query.per_page as i64)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok((records, total))
+    }
 }
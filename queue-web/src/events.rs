@@ -0,0 +1,104 @@
+use anyhow::Result;
+use chrono::{NaiveDate, NaiveTime};
+use queue_core::enrollee::Status;
+use serde::{Deserialize, Serialize};
+use sqlx::postgres::PgListener;
+use std::convert::Infallible;
+use tokio::sync::broadcast::{self, error::RecvError, Receiver, Sender};
+use warp::sse::Event;
+
+const CHANNEL: &str = "queue_events";
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EventKind {
+    Booked,
+    Cancelled,
+    Edited,
+    Status,
+}
+
+/// Change of the queue, sent by the `notify_queue_event` trigger.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueueEvent {
+    pub kind: EventKind,
+    pub enrollee: i32,
+    pub date: NaiveDate,
+    pub time: NaiveTime,
+    pub status: Status,
+    pub desk: Option<i32>,
+    /// Date the booking was moved from.
+    #[serde(default)]
+    pub old_date: Option<NaiveDate>,
+}
+
+/// Fans the notifications of a single database listener out to every connected operator.
+pub struct Events {
+    sender: Sender<QueueEvent>,
+}
+
+impl Events {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(256);
+        Events { sender }
+    }
+
+    pub fn subscribe(&self) -> Receiver<QueueEvent> {
+        self.sender.subscribe()
+    }
+
+    pub async fn run(&self, database_url: &str) -> Result<()> {
+        let mut listener = PgListener::connect(database_url).await?;
+        listener.listen(CHANNEL).await?;
+        loop {
+            // The listener reconnects by itself, an error means that reconnecting failed too
+            let notification = match listener.recv().await {
+                Ok(notification) => notification,
+                Err(error) => {
+                    log::error!("Failed to receive queue event: {}", error);
+                    tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+                    continue;
+                }
+            };
+            match serde_json::from_str::<QueueEvent>(notification.payload()) {
+                // Sending fails only when nobody is subscribed
+                Ok(event) => drop(self.sender.send(event)),
+                Err(error) => log::error!("Failed to parse queue event: {}", error),
+            }
+        }
+    }
+}
+
+/// Server-sent events of the receiver, `map` picks the events the client gets and what
+/// it sees of them. A client that falls behind skips the missed events, it is expected
+/// to reload the board on the `lagged` event.
+pub fn stream<T, F>(
+    receiver: Receiver<QueueEvent>,
+    map: F,
+) -> impl futures::Stream<Item = Result<Event, Infallible>>
+where
+    T: Serialize,
+    F: Fn(&QueueEvent) -> Option<T>,
+{
+    futures::stream::unfold((receiver, map), |(mut receiver, map)| async move {
+        let event = loop {
+            match receiver.recv().await {
+                Ok(event) => {
+                    if let Some(data) = map(&event) {
+                        let kind = serde_json::to_value(event.kind).unwrap_or_default();
+                        break Event::default()
+                            .event(kind.as_str().unwrap_or_default())
+                            .json_data(&data)
+                            .unwrap_or_default();
+                    }
+                }
+                Err(RecvError::Lagged(count)) => {
+                    break Event::default().event("lagged").data(count.to_string())
+                }
+                Err(RecvError::Closed) => return None,
+            }
+        };
+        Some((Ok(event), (receiver, map)))
+    })
+}
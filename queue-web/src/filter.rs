@@ -1,12 +1,14 @@
 use warp::{Filter, Rejection, Reply};
 
-use crate::handlers::{admin, user};
-use crate::model::enrollee::Status;
-use crate::model::user::Role;
+use crate::handlers::{admin, public, user};
+use crate::model::user::Permission;
 use crate::Application;
 use chrono::NaiveDate;
+use queue_core::enrollee::Status;
+use std::net::{IpAddr, SocketAddr};
+use uuid::Uuid;
 
-mod jwt;
+pub mod jwt;
 
 pub fn routes(
     app: &'static Application,
@@ -15,6 +17,21 @@ pub fn routes(
         .map(|name| format!("Hello, {}!", name))
         .or(user_routes(app))
         .or(admin_routes(app))
+        .or(public_routes(app))
+}
+
+fn public_routes(
+    app: &'static Application,
+) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    let board = warp::path!("board" / NaiveDate)
+        .and(warp::get())
+        .and(with_app(app))
+        .and_then(public::board);
+    let board_events = warp::path!("board" / NaiveDate / "events")
+        .and(warp::get())
+        .and(with_app(app))
+        .and_then(public::board_events);
+    warp::path("public").and(board.or(board_events))
 }
 
 fn user_routes(
@@ -31,9 +48,15 @@ fn auth_routes(
         .and(warp::body::json())
         .and(with_app(app))
         .and_then(user::auth::register);
+    let captcha = warp::path("captcha")
+        .and(warp::get())
+        .and(client_ip(app))
+        .and(with_app(app))
+        .and_then(user::auth::captcha);
     let login = warp::path("login")
         .and(warp::post())
         .and(warp::body::json())
+        .and(client_ip(app))
         .and(with_app(app))
         .and_then(user::auth::login);
     let logout = warp::path("logout")
@@ -47,14 +70,118 @@ fn auth_routes(
         .and(with_app(app))
         .and(jwt::refresh_filter())
         .and_then(user::auth::refresh_session);
-    let routes = register.or(login).or(logout).or(refresh_session);
+    let verify = warp::path("verify")
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(with_app(app))
+        .and_then(user::auth::verify);
+    let forgot = warp::path("forgot")
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(with_app(app))
+        .and_then(user::auth::forgot);
+    let reset = warp::path("reset")
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(with_app(app))
+        .and_then(user::auth::reset);
+    let sessions = warp::path!("sessions")
+        .and(warp::get())
+        .and(with_app(app))
+        .and(jwt::jwt_filter(app, vec![]))
+        .and(jwt::optional_refresh_filter())
+        .and_then(user::auth::sessions);
+    let revoke_session = warp::path!("sessions" / Uuid)
+        .and(warp::delete())
+        .and(with_app(app))
+        .and(jwt::jwt_filter(app, vec![]))
+        .and_then(user::auth::revoke_session);
+    let routes = register
+        .or(captcha)
+        .or(login)
+        .or(logout)
+        .or(refresh_session)
+        .or(verify)
+        .or(forgot)
+        .or(reset)
+        .or(sessions)
+        .or(revoke_session);
     warp::path("auth").and(routes)
 }
 
 fn admin_routes(
     app: &'static Application,
 ) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
-    warp::path("admin").and(queue_routes(app))
+    let audit = warp::path("audit")
+        .and(warp::path::end())
+        .and(warp::get())
+        .and(warp::query())
+        .and(with_app(app))
+        .and(jwt::jwt_filter(app, vec![Permission::ViewAudit]))
+        .and_then(admin::audit::list);
+    warp::path("admin").and(
+        queue_routes(app)
+            .or(schedule_routes(app))
+            .or(users_routes(app))
+            .or(audit),
+    )
+}
+
+fn users_routes(
+    app: &'static Application,
+) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    let list = warp::path::end()
+        .and(warp::get())
+        .and(with_app(app))
+        .and(jwt::jwt_filter(app, vec![Permission::ManageUsers]))
+        .and_then(admin::users::list);
+    let create = warp::path::end()
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(with_app(app))
+        .and(jwt::jwt_filter(app, vec![Permission::ManageUsers]))
+        .and_then(admin::users::create);
+    let role = warp::path!(i32 / "role")
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(with_app(app))
+        .and(jwt::jwt_filter(app, vec![Permission::ManageUsers]))
+        .and_then(admin::users::role);
+    let disable = warp::path!(i32 / "disable")
+        .and(warp::post())
+        .and(with_app(app))
+        .and(jwt::jwt_filter(app, vec![Permission::ManageUsers]))
+        .and_then(admin::users::disable);
+    let enable = warp::path!(i32 / "enable")
+        .and(warp::post())
+        .and(with_app(app))
+        .and(jwt::jwt_filter(app, vec![Permission::ManageUsers]))
+        .and_then(admin::users::enable);
+    let logout = warp::path!(i32 / "logout")
+        .and(warp::post())
+        .and(with_app(app))
+        .and(jwt::jwt_filter(app, vec![Permission::ManageUsers]))
+        .and_then(admin::users::logout);
+    let password = warp::path!(i32 / "password")
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(with_app(app))
+        .and(jwt::jwt_filter(app, vec![Permission::ManageUsers]))
+        .and_then(admin::users::password);
+    let delete = warp::path!(i32)
+        .and(warp::delete())
+        .and(with_app(app))
+        .and(jwt::jwt_filter(app, vec![Permission::ManageUsers]))
+        .and_then(admin::users::delete);
+    let routes = list
+        .or(create)
+        .or(role)
+        .or(disable)
+        .or(enable)
+        .or(logout)
+        .or(password)
+        .or(delete);
+    warp::path("users").and(routes)
 }
 
 fn queue_routes(
@@ -63,51 +190,108 @@ fn queue_routes(
     let dates = warp::path("dates")
         .and(warp::get())
         .and(with_app(app))
-        .and(jwt::jwt_filter(app, vec![Role::Admin]))
+        .and(jwt::jwt_filter(app, vec![Permission::ViewQueue]))
         .and_then(admin::queue::dates);
     let enrollees = warp::path("enrollees")
         .and(warp::post())
         .and(warp::body::json())
         .and(with_app(app))
-        .and(jwt::jwt_filter(app, vec![Role::Admin]))
+        .and(jwt::jwt_filter(app, vec![Permission::ViewQueue]))
         .and_then(admin::queue::enrollees);
-    let processed = warp::path!("status" / i64 / Status)
+    let processed = warp::path!("status" / i32 / Status)
+        .and(warp::query())
         .and(with_app(app))
-        .and(jwt::jwt_filter(app, vec![Role::Admin]))
+        .and(jwt::jwt_filter(app, vec![Permission::ChangeStatus]))
         .and_then(admin::queue::status);
+    let events = warp::path("events")
+        .and(warp::get())
+        .and(with_app(app))
+        .and(jwt::jwt_stream_filter(app, vec![Permission::ViewQueue]))
+        .and_then(admin::queue::events);
     let update = warp::path("update")
         .and(warp::post())
         .and(warp::body::json())
         .and(with_app(app))
-        .and(jwt::jwt_filter(app, vec![Role::Admin]))
+        .and(jwt::jwt_filter(app, vec![Permission::EditQueue]))
         .and_then(admin::queue::update);
     let students_queue = warp::path("students-queue")
         .and(warp::get())
         .and(with_app(app))
-        .and(jwt::jwt_filter(app, vec![Role::Admin]))
+        .and(jwt::jwt_filter(app, vec![Permission::Export]))
         .and_then(admin::queue::students_queue);
     let relevant_time = warp::path!("relevant-time" / NaiveDate)
         .and(with_app(app))
-        .and(jwt::jwt_filter(app, vec![Role::Admin]))
+        .and(jwt::jwt_filter(app, vec![Permission::ViewQueue]))
         .and_then(admin::queue::relevant_time);
     let register = warp::path("register")
         .and(warp::post())
         .and(warp::body::json())
         .and(with_app(app))
-        .and(jwt::jwt_filter(app, vec![Role::Admin]))
+        .and(jwt::jwt_filter(app, vec![Permission::EditQueue]))
         .and_then(admin::queue::register);
+    let applications = warp::path!("applications" / i32)
+        .and(warp::get())
+        .and(with_app(app))
+        .and(jwt::jwt_filter(app, vec![Permission::ViewQueue]))
+        .and_then(admin::queue::applications);
     let routes = dates
         .or(enrollees)
         .or(processed)
+        .or(events)
         .or(update)
         .or(students_queue)
         .or(relevant_time)
-        .or(register);
+        .or(register)
+        .or(applications);
     warp::path("queue").and(routes)
 }
 
+fn schedule_routes(
+    app: &'static Application,
+) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    let list = warp::path::end()
+        .and(warp::get())
+        .and(with_app(app))
+        .and(jwt::jwt_filter(app, vec![Permission::ManageSchedule]))
+        .and_then(admin::schedule::list);
+    let update = warp::path::end()
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(with_app(app))
+        .and(jwt::jwt_filter(app, vec![Permission::ManageSchedule]))
+        .and_then(admin::schedule::update);
+    let delete = warp::path!(NaiveDate)
+        .and(warp::delete())
+        .and(with_app(app))
+        .and(jwt::jwt_filter(app, vec![Permission::ManageSchedule]))
+        .and_then(admin::schedule::delete);
+    let routes = list.or(update).or(delete);
+    warp::path("schedule").and(routes)
+}
+
 pub fn with_app(
     app: &'static Application,
-) -> impl Filter<Extract = (&Application,), Error = std::convert::Infallible> + Clone {
+) -> impl Filter<Extract = (&'static Application,), Error = std::convert::Infallible> + Clone {
     warp::any().map(move || app)
 }
+
+/// Peer address of the connection, put into the request extensions in `main`.
+#[derive(Clone, Copy)]
+pub struct ClientAddr(pub SocketAddr);
+
+/// With `trustForwardedFor` the address comes from the last `X-Forwarded-For` entry, the
+/// one the proxy in front added. Entries before it are sent by the client and can be forged.
+fn client_ip(
+    app: &'static Application,
+) -> impl Filter<Extract = (Option<IpAddr>,), Error = Rejection> + Clone {
+    warp::header::optional::<String>("x-forwarded-for")
+        .and(warp::ext::optional::<ClientAddr>())
+        .map(
+            move |forwarded: Option<String>, address: Option<ClientAddr>| {
+                forwarded
+                    .filter(|_| app.throttle.trust_forwarded_for)
+                    .and_then(|forwarded| forwarded.rsplit(',').next()?.trim().parse().ok())
+                    .or_else(|| address.map(|address| address.0.ip()))
+            },
+        )
+}
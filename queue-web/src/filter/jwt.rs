@@ -1,16 +1,49 @@
+use serde::Deserialize;
+use std::convert::Infallible;
+use std::time::Duration;
 use uuid::Uuid;
 use warp::Filter;
 
 use crate::filter;
-use crate::model::user::{AuthInfo, Role};
+use crate::model::user::{AuthInfo, Permission};
 use crate::Application;
 use crate::{reject, reject_result};
 use chrono::Utc;
 
+/// How often an open stream checks that its account may still read it.
+const STREAM_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// The account behind a token as it is now, so a disabled account or a changed role
+/// applies before the token expires.
+async fn current_user(
+    app: &Application,
+    user_id: i32,
+    permissions: &[Permission],
+) -> Result<AuthInfo, warp::Rejection> {
+    let user = match reject_result!(app.database.get_user_by_id(user_id).await) {
+        Some(user) => user,
+        None => reject!("error-session-expired", 401),
+    };
+    if user.disabled {
+        reject!("error-account-disabled", 403);
+    }
+    if !permissions
+        .iter()
+        .all(|permission| user.role.can(*permission))
+    {
+        reject!("error-forbidden", 403)
+    }
+    Ok(AuthInfo {
+        id: user.id,
+        username: user.username,
+        role: user.role,
+    })
+}
+
 async fn auth(
     header: Option<String>,
     app: &Application,
-    roles: Vec<Role>,
+    permissions: Vec<Permission>,
 ) -> Result<AuthInfo, warp::Rejection> {
     if let Some(bearer_string) = header {
         let header: Vec<String> = bearer_string
@@ -18,31 +51,62 @@ async fn auth(
             .map(String::from)
             .collect();
         if header.len() != 2 || header[0] != "Bearer" {
-            reject!("Token format error");
+            reject!("error-token-format");
         }
         let access_token = reject_result!(app.jwt.decode_token(&header[1]));
         if Utc::now().timestamp() >= access_token.exp {
-            reject!("This session has been expired", 401);
-        }
-        if !roles.is_empty() && !roles.contains(&access_token.role) {
-            reject!("Forbidden", 403)
+            reject!("error-session-expired", 401);
         }
-        Ok(AuthInfo::from(access_token))
+        current_user(app, access_token.sub, &permissions).await
     } else {
-        reject!("Not auth", 401);
+        reject!("error-not-auth", 401);
     }
 }
 
+/// Passes if the token's role has every permission, an empty list lets any signed in user in.
 pub fn jwt_filter(
     app: &'static Application,
-    roles: Vec<Role>,
+    permissions: Vec<Permission>,
+) -> impl Filter<Extract = (AuthInfo,), Error = warp::Rejection> + Clone {
+    warp::header::optional::<String>("authorization")
+        .and(filter::with_app(app))
+        .and(warp::any().map(move || permissions.clone()))
+        .and_then(auth)
+}
+
+#[derive(Deserialize)]
+struct TokenQuery {
+    token: Option<String>,
+}
+
+/// `EventSource` in browsers can't set headers, so streams also take the access token
+/// from the `token` query parameter.
+pub fn jwt_stream_filter(
+    app: &'static Application,
+    permissions: Vec<Permission>,
 ) -> impl Filter<Extract = (AuthInfo,), Error = warp::Rejection> + Clone {
     warp::header::optional::<String>("authorization")
+        .and(warp::query::<TokenQuery>())
+        .map(|header: Option<String>, query: TokenQuery| {
+            header.or_else(|| query.token.map(|token| format!("Bearer {}", token)))
+        })
         .and(filter::with_app(app))
-        .and(warp::any().map(move || roles.clone()))
+        .and(warp::any().map(move || permissions.clone()))
         .and_then(auth)
 }
 
+/// Resolves once the account can't pass the stream filter anymore, streams outlive
+/// the token they were opened with and end with this.
+pub async fn revoked(app: &Application, user_id: i32, permissions: Vec<Permission>) {
+    let mut interval = tokio::time::interval(STREAM_CHECK_INTERVAL);
+    loop {
+        interval.tick().await;
+        if current_user(app, user_id, &permissions).await.is_err() {
+            return;
+        }
+    }
+}
+
 pub fn refresh_filter() -> impl Filter<Extract = (Uuid,), Error = warp::Rejection> + Clone {
     warp::filters::cookie::optional("refreshToken").and_then(|token: Option<String>| async move {
         if let Some(token) = token {
@@ -50,7 +114,14 @@ pub fn refresh_filter() -> impl Filter<Extract = (Uuid,), Error = warp::Rejectio
                 Uuid::parse_str(&token).map_err(|error| anyhow::anyhow!("{:?}", error))
             ))
         } else {
-            reject!("Not auth", 401);
+            reject!("error-not-auth", 401);
         }
     })
 }
+
+/// The refresh token if the cookie is there and well formed.
+pub fn optional_refresh_filter(
+) -> impl Filter<Extract = (Option<Uuid>,), Error = Infallible> + Clone {
+    warp::filters::cookie::optional("refreshToken")
+        .map(|token: Option<String>| token.and_then(|token| Uuid::parse_str(&token).ok()))
+}
@@ -1 +1,4 @@
+pub mod audit;
 pub mod queue;
+pub mod schedule;
+pub mod users;
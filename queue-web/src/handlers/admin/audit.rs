@@ -0,0 +1,23 @@
+use warp::Reply;
+
+use crate::model::audit::AuditQuery;
+use crate::model::user::AuthInfo;
+use crate::Application;
+use crate::{reject, reject_result};
+
+const MAX_PER_PAGE: u32 = 200;
+
+pub async fn list(
+    mut query: AuditQuery,
+    app: &'static Application,
+    _auth_info: AuthInfo,
+) -> Result<impl Reply, warp::Rejection> {
+    query.per_page = query.per_page.clamp(1, MAX_PER_PAGE);
+    let (records, total) = reject_result!(app.database.get_audit_log(&query).await);
+    Ok(warp::reply::json(&serde_json::json!({
+        "records": records,
+        "total": total,
+        "page": query.page,
+        "perPage": query.per_page
+    })))
+}
@@ -1,11 +1,14 @@
 use chrono::NaiveDate;
+use futures::StreamExt;
 use serde::Deserialize;
 use warp::Reply;
 
-use crate::model::enrollee::{Enrollee, Status};
-use crate::model::user::AuthInfo;
+use crate::events;
+use crate::filter::jwt;
+use crate::model::user::{AuthInfo, Permission};
 use crate::Application;
 use crate::{reject, reject_result};
+use queue_core::enrollee::{Enrollee, Status};
 
 pub async fn dates(
     app: &'static Application,
@@ -26,22 +29,41 @@ pub async fn enrollees(
     })))
 }
 
+#[derive(Deserialize)]
+pub struct DeskQuery {
+    pub desk: Option<i32>,
+}
+
 pub async fn status(
-    id: i64,
+    id: i32,
     status: Status,
+    query: DeskQuery,
     app: &'static Application,
-    _auth_info: AuthInfo,
+    auth_info: AuthInfo,
 ) -> Result<impl Reply, warp::Rejection> {
-    reject_result!(app.database.change_status(id, status).await);
-    Ok(warp::reply::reply())
+    let desk = reject_result!(
+        app.database
+            .change_status(&auth_info, id, status, query.desk)
+            .await
+    );
+    Ok(warp::reply::json(&serde_json::json!({ "desk": desk })))
+}
+
+pub async fn events(
+    app: &'static Application,
+    auth_info: AuthInfo,
+) -> Result<impl Reply, warp::Rejection> {
+    let stream = events::stream(app.events.subscribe(), |event| Some(event.clone()))
+        .take_until(jwt::revoked(app, auth_info.id, vec![Permission::ViewQueue]));
+    Ok(warp::sse::reply(warp::sse::keep_alive().stream(stream)))
 }
 
 pub async fn update(
     enrollee: Enrollee,
     app: &'static Application,
-    _auth_info: AuthInfo,
+    auth_info: AuthInfo,
 ) -> Result<impl Reply, warp::Rejection> {
-    reject_result!(app.database.update_enrollee(enrollee).await);
+    reject_result!(app.database.update_enrollee(&auth_info, enrollee).await);
     Ok(warp::reply::reply())
 }
 
@@ -66,6 +88,15 @@ pub async fn relevant_time(
     })))
 }
 
+pub async fn applications(
+    id: i32,
+    app: &'static Application,
+    _auth_info: AuthInfo,
+) -> Result<impl Reply, warp::Rejection> {
+    let applications = reject_result!(app.database.get_applications(id).await);
+    Ok(warp::reply::json(&applications))
+}
+
 #[derive(Deserialize)]
 pub struct QueueInfo {
     pub last_name: String,
@@ -79,8 +110,8 @@ pub struct QueueInfo {
 pub async fn register(
     info: QueueInfo,
     app: &'static Application,
-    _auth_info: AuthInfo,
+    auth_info: AuthInfo,
 ) -> Result<impl Reply, warp::Rejection> {
-    let id = reject_result!(app.database.register_in_queue(info).await);
+    let id = reject_result!(app.database.register_in_queue(&auth_info, info).await);
     Ok(warp::reply::json(&serde_json::json!({ "id": id })))
 }
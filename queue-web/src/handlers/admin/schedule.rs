@@ -0,0 +1,36 @@
+use chrono::NaiveDate;
+use queue_core::schedule::ScheduleDay;
+use warp::Reply;
+
+use crate::model::user::AuthInfo;
+use crate::Application;
+use crate::{reject, reject_result};
+
+pub async fn list(
+    app: &'static Application,
+    _auth_info: AuthInfo,
+) -> Result<impl Reply, warp::Rejection> {
+    let schedule = reject_result!(app.database.get_schedule().await);
+    Ok(warp::reply::json(
+        &serde_json::json!({ "schedule": schedule }),
+    ))
+}
+
+pub async fn update(
+    day: ScheduleDay,
+    app: &'static Application,
+    auth_info: AuthInfo,
+) -> Result<impl Reply, warp::Rejection> {
+    reject_result!(day.schedule.validate());
+    reject_result!(app.database.update_schedule_day(&auth_info, day).await);
+    Ok(warp::reply::reply())
+}
+
+pub async fn delete(
+    date: NaiveDate,
+    app: &'static Application,
+    auth_info: AuthInfo,
+) -> Result<impl Reply, warp::Rejection> {
+    reject_result!(app.database.delete_schedule_day(&auth_info, date).await);
+    Ok(warp::reply::reply())
+}
@@ -0,0 +1,127 @@
+use serde::Deserialize;
+use warp::Reply;
+
+use crate::model::user::{AuthInfo, NewUser, Role};
+use crate::Application;
+use crate::{reject, reject_if, reject_if_negative, reject_result};
+
+pub async fn list(
+    app: &'static Application,
+    _auth_info: AuthInfo,
+) -> Result<impl Reply, warp::Rejection> {
+    let users = reject_result!(app.database.get_users().await);
+    Ok(warp::reply::json(&serde_json::json!({ "users": users })))
+}
+
+pub async fn create(
+    user: NewUser,
+    app: &'static Application,
+    auth_info: AuthInfo,
+) -> Result<impl Reply, warp::Rejection> {
+    reject_if!(
+        app.database.user_exists(&user.username).await,
+        "error-username-in-use"
+    );
+    let id = reject_result!(app.database.create_staff_user(&auth_info, user).await);
+    Ok(warp::reply::json(&serde_json::json!({ "id": id })))
+}
+
+#[derive(Deserialize)]
+pub struct RoleInfo {
+    pub role: Role,
+}
+
+pub async fn role(
+    id: i32,
+    info: RoleInfo,
+    app: &'static Application,
+    auth_info: AuthInfo,
+) -> Result<impl Reply, warp::Rejection> {
+    // An admin demoting themselves could leave nobody able to manage accounts
+    if id == auth_info.id {
+        reject!("error-own-account");
+    }
+    reject_if_negative!(
+        app.database.change_role(&auth_info, id, info.role).await,
+        "error-account-id-not-found",
+        404
+    );
+    Ok(warp::reply::reply())
+}
+
+pub async fn disable(
+    id: i32,
+    app: &'static Application,
+    auth_info: AuthInfo,
+) -> Result<impl Reply, warp::Rejection> {
+    if id == auth_info.id {
+        reject!("error-own-account");
+    }
+    reject_if_negative!(
+        app.database.set_user_disabled(&auth_info, id, true).await,
+        "error-account-id-not-found",
+        404
+    );
+    Ok(warp::reply::reply())
+}
+
+pub async fn enable(
+    id: i32,
+    app: &'static Application,
+    auth_info: AuthInfo,
+) -> Result<impl Reply, warp::Rejection> {
+    reject_if_negative!(
+        app.database.set_user_disabled(&auth_info, id, false).await,
+        "error-account-id-not-found",
+        404
+    );
+    Ok(warp::reply::reply())
+}
+
+pub async fn delete(
+    id: i32,
+    app: &'static Application,
+    auth_info: AuthInfo,
+) -> Result<impl Reply, warp::Rejection> {
+    if id == auth_info.id {
+        reject!("error-own-account");
+    }
+    reject_if_negative!(
+        app.database.delete_user(&auth_info, id).await,
+        "error-account-id-not-found",
+        404
+    );
+    Ok(warp::reply::reply())
+}
+
+pub async fn logout(
+    id: i32,
+    app: &'static Application,
+    auth_info: AuthInfo,
+) -> Result<impl Reply, warp::Rejection> {
+    let sessions = reject_result!(app.database.remove_user_sessions(&auth_info, id).await);
+    Ok(warp::reply::json(
+        &serde_json::json!({ "sessions": sessions }),
+    ))
+}
+
+#[derive(Deserialize)]
+pub struct PasswordInfo {
+    pub password: String,
+}
+
+pub async fn password(
+    id: i32,
+    info: PasswordInfo,
+    app: &'static Application,
+    auth_info: AuthInfo,
+) -> Result<impl Reply, warp::Rejection> {
+    reject_if_negative!(
+        app.database
+            .reset_user_password(&auth_info, id, &info.password)
+            .await,
+        "error-account-id-not-found",
+        404
+    );
+    Ok(warp::reply::reply())
+}
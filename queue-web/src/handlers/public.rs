@@ -0,0 +1,32 @@
+use chrono::NaiveDate;
+use warp::Reply;
+
+use crate::events;
+use crate::model::board::BoardEvent;
+use crate::Application;
+use crate::{reject, reject_result};
+
+pub async fn board(
+    date: NaiveDate,
+    app: &'static Application,
+) -> Result<impl Reply, warp::Rejection> {
+    let entries = reject_result!(app.database.get_board(date).await);
+    Ok(warp::reply::json(&serde_json::json!({
+        "date": date,
+        "entries": entries
+    })))
+}
+
+pub async fn board_events(
+    date: NaiveDate,
+    app: &'static Application,
+) -> Result<impl Reply, warp::Rejection> {
+    let stream = events::stream(app.events.subscribe(), move |event| {
+        if event.date == date || event.old_date == Some(date) {
+            Some(BoardEvent::from(event))
+        } else {
+            None
+        }
+    });
+    Ok(warp::sse::reply(warp::sse::keep_alive().stream(stream)))
+}
@@ -1,11 +1,18 @@
-use anyhow::{Context, Result};
+use anyhow::Result;
+use chrono::{Duration, Utc};
+use queue_core::i18n::{Language, LocalizedError};
+use queue_core::tr;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::net::IpAddr;
 use uuid::Uuid;
 use warp::Reply;
 
 use crate::hash;
-use crate::model::user::AuthInfo;
+use crate::language;
+use crate::mail::Mailer;
+use crate::model::user::{AuthInfo, TokenKind};
+use crate::throttle::Limits;
 use crate::Application;
 use crate::{reject, reject_if, reject_if_negative, reject_result};
 
@@ -21,15 +28,182 @@ pub async fn register(
     info: RegistrationInfo,
     app: &'static Application,
 ) -> Result<impl Reply, warp::Rejection> {
-    reject_if_negative!(
-        app.recaptcha.check(&info.token).await,
-        "Failed to verify captcha"
-    );
+    if !app.open_registration {
+        reject!("error-registration-closed", 403);
+    }
+    reject_if_negative!(app.captcha.check(&info.token).await, "error-captcha");
     reject_if!(
         app.database.user_exists(&info.username).await,
-        "This username already in use"
+        "error-username-in-use"
+    );
+    let email = info.email.clone();
+    let id = reject_result!(app.database.create_user(info, app.mailer.is_none()).await);
+    if let Some(mailer) = &app.mailer {
+        let sent = send_verification(app, mailer, id, &email).await;
+        if let Err(error) = sent {
+            log::error!("Failed to send verification email: {}", error);
+            reject_result!(app.database.remove_unverified_user(id).await);
+            reject!("error-mail-failed", 500);
+        }
+    }
+    Ok(warp::reply::reply())
+}
+
+/// A new image for the self-hosted captcha. Drawing it is slow, so every address may
+/// request only `challengesPerIp` of them within the lockout window.
+pub async fn captcha(
+    client: Option<IpAddr>,
+    app: &'static Application,
+) -> Result<impl Reply, warp::Rejection> {
+    if let Some(client) = client {
+        let limits = Limits {
+            backoff_after: app.throttle.challenges_per_ip,
+            lockout_after: app.throttle.challenges_per_ip,
+        };
+        let counted = reject_result!(
+            app.database
+                .reserve_login_attempt(&[format!("captcha:{}", client)], |requests| {
+                    app.throttle.attempt(requests, &[limits], Utc::now())
+                })
+                .await
+        );
+        if counted.is_err() {
+            reject!("error-captcha-throttled", 429);
+        }
+    }
+    let challenge = tokio::task::spawn_blocking(move || app.captcha.challenge())
+        .await
+        .map_err(anyhow::Error::from)
+        .and_then(|challenge| challenge);
+    match reject_result!(challenge) {
+        Some(challenge) => Ok(warp::reply::json(&challenge)),
+        None => reject!("error-captcha-no-challenge", 404),
+    }
+}
+
+const VERIFICATION_LIFETIME_HOURS: i64 = 48;
+const RESET_LIFETIME_MINUTES: i64 = 60;
+
+async fn send_verification(
+    app: &Application,
+    mailer: &Mailer,
+    user_id: i32,
+    email: &str,
+) -> Result<()> {
+    let token = app
+        .database
+        .create_user_token(
+            user_id,
+            TokenKind::VerifyEmail,
+            Duration::hours(VERIFICATION_LIFETIME_HOURS),
+        )
+        .await?;
+    let language = language::current();
+    mailer
+        .send(
+            email,
+            tr!(language, "mail-verify-subject"),
+            tr!(
+                language,
+                "mail-verify-body",
+                link = mailer.link("verify", token)
+            ),
+        )
+        .await
+}
+
+#[derive(Deserialize)]
+pub struct TokenInfo {
+    pub token: Uuid,
+}
+
+pub async fn verify(
+    info: TokenInfo,
+    app: &'static Application,
+) -> Result<impl Reply, warp::Rejection> {
+    reject_if_negative!(
+        app.database.verify_email(info.token).await,
+        "error-token-invalid"
+    );
+    Ok(warp::reply::reply())
+}
+
+#[derive(Deserialize)]
+pub struct ForgotInfo {
+    pub email: String,
+    pub token: String,
+}
+
+/// Answers the same whether the account exists or not, so emails can't be probed. The
+/// letter is sent in the background, neither the time of the answer nor a failed send
+/// tell the accounts apart.
+pub async fn forgot(
+    info: ForgotInfo,
+    app: &'static Application,
+) -> Result<impl Reply, warp::Rejection> {
+    let mailer = match &app.mailer {
+        Some(mailer) => mailer,
+        None => reject!("error-mail-disabled"),
+    };
+    reject_if_negative!(app.captcha.check(&info.token).await, "error-captcha");
+    let language = language::current();
+    tokio::spawn(async move {
+        if let Err(error) = send_reset(app, mailer, &info.email, language).await {
+            log::error!("Failed to send password reset email: {}", error);
+        }
+    });
+    Ok(warp::reply::reply())
+}
+
+async fn send_reset(
+    app: &Application,
+    mailer: &Mailer,
+    email: &str,
+    language: Language,
+) -> Result<()> {
+    let user = match app.database.get_user_by_email(email).await? {
+        Some(user) if !user.disabled => user,
+        _ => return Ok(()),
+    };
+    let token = app
+        .database
+        .create_user_token(
+            user.id,
+            TokenKind::ResetPassword,
+            Duration::minutes(RESET_LIFETIME_MINUTES),
+        )
+        .await?;
+    mailer
+        .send(
+            &user.email,
+            tr!(language, "mail-reset-subject"),
+            tr!(
+                language,
+                "mail-reset-body",
+                username = user.username,
+                link = mailer.link("reset", token),
+                minutes = RESET_LIFETIME_MINUTES
+            ),
+        )
+        .await
+}
+
+#[derive(Deserialize)]
+pub struct ResetInfo {
+    pub token: Uuid,
+    pub password: String,
+}
+
+pub async fn reset(
+    info: ResetInfo,
+    app: &'static Application,
+) -> Result<impl Reply, warp::Rejection> {
+    reject_if_negative!(
+        app.database
+            .reset_password(info.token, &info.password)
+            .await,
+        "error-token-invalid"
     );
-    reject_result!(app.database.create_user(info).await);
     Ok(warp::reply::reply())
 }
 
@@ -38,26 +212,83 @@ pub struct LoginInfo {
     pub username: String,
     pub password: String,
     pub fingerprint: String,
+    /// reCAPTCHA response, needed after `captchaAfter` failures.
+    #[serde(default)]
+    pub token: Option<String>,
 }
 
+/// Both the username and the client address count failures, an unknown account and a wrong
+/// password look the same to the client.
 pub async fn login(
     info: LoginInfo,
+    client: Option<IpAddr>,
     app: &'static Application,
 ) -> Result<impl Reply, warp::Rejection> {
-    if let Some(user) = reject_result!(app.database.get_user_by_name(&info.username).await) {
-        reject_if_negative!(
-            hash::verify_password(&info.password, &user.password),
-            "Incorrect password"
-        );
-        let refresh_session = reject_result!(
-            app.database
-                .create_refresh_session(user.id, &info.fingerprint)
-                .await
+    let throttle = &app.throttle;
+    let mut keys = vec![format!("user:{}", info.username)];
+    let mut limits = vec![throttle.username];
+    if let Some(client) = client {
+        keys.push(format!("ip:{}", client));
+        limits.push(throttle.ip);
+    }
+    // The attempt is counted before the password is checked and taken back on success,
+    // an attempt turned away here isn't counted.
+    let counted = app.database.reserve_login_attempt(&keys, |failures| {
+        let now = Utc::now();
+        throttle
+            .attempt(failures, &limits, now)
+            .map_err(|retry_at| {
+                let seconds = ((retry_at - now).num_milliseconds() + 999) / 1000;
+                let error = LocalizedError::new("error-login-throttled").arg("seconds", seconds);
+                reject::Error::from((error, 429))
+            })?;
+        // Failures before this attempt
+        let most_failures = failures.iter().map(|item| item.failures - 1).max();
+        let captcha = matches!(
+            (throttle.captcha_after, most_failures),
+            (Some(after), Some(most)) if most >= after
         );
-        app.jwt.create_session_reply(refresh_session, user.into())
-    } else {
-        reject!("Failed to find account with this username");
+        if captcha && info.token.is_none() {
+            return Err(reject::Error::from(("error-captcha-required", 428)));
+        }
+        Ok(captcha)
+    });
+    let captcha = match reject_result!(counted.await) {
+        Ok(captcha) => captcha,
+        Err(error) => return Err(warp::reject::custom(error)),
+    };
+    if let (true, Some(token)) = (captcha, &info.token) {
+        reject_if_negative!(app.captcha.check(token).await, "error-captcha");
+    }
+
+    let user = reject_result!(app.database.get_user_by_name(&info.username).await);
+    let verified = match &user {
+        Some(user) => reject_result!(hash::verify_password(&info.password, &user.password)),
+        None => {
+            hash::verify_dummy(&info.password);
+            false
+        }
+    };
+    let user = match user {
+        Some(user) if verified => user,
+        _ => reject!("error-login-failed"),
+    };
+    reject_result!(app.database.clear_login_failures(&keys[0]).await);
+    for key in &keys[1..] {
+        reject_result!(app.database.release_login_attempt(key).await);
+    }
+    if user.disabled {
+        reject!("error-account-disabled", 403);
     }
+    if !user.email_verified {
+        reject!("error-email-not-verified", 403);
+    }
+    let refresh_session = reject_result!(
+        app.database
+            .create_refresh_session(user.id, &info.fingerprint, app.jwt.refresh_lifetime())
+            .await
+    );
+    app.jwt.create_session_reply(refresh_session, user.into())
 }
 
 pub async fn logout(
@@ -73,6 +304,26 @@ pub async fn logout(
     app.jwt.invalidate_session()
 }
 
+pub async fn sessions(
+    app: &'static Application,
+    auth_info: AuthInfo,
+    refresh_token: Option<Uuid>,
+) -> Result<impl Reply, warp::Rejection> {
+    let sessions = reject_result!(app.database.get_sessions(auth_info.id, refresh_token).await);
+    Ok(warp::reply::json(&sessions))
+}
+
+pub async fn revoke_session(
+    id: Uuid,
+    app: &'static Application,
+    auth_info: AuthInfo,
+) -> Result<impl Reply, warp::Rejection> {
+    if !reject_result!(app.database.revoke_session(auth_info.id, id).await) {
+        reject!("error-session-not-found", 404);
+    }
+    Ok(warp::reply::reply())
+}
+
 pub async fn refresh_session(
     fingerprint: Value,
     app: &'static Application,
@@ -80,20 +331,24 @@ pub async fn refresh_session(
 ) -> Result<impl Reply, warp::Rejection> {
     let option_fingerprint = reject_result!(fingerprint
         .get("fingerprint")
-        .context("Failed to get fingerprint"))
+        .ok_or_else(|| LocalizedError::new("error-fingerprint-missing")))
     .as_str();
-    let fingerprint =
-        reject_result!(option_fingerprint.context("Failed to parse fingerprint as string"));
+    let fingerprint = reject_result!(
+        option_fingerprint.ok_or_else(|| LocalizedError::new("error-fingerprint-format"))
+    );
     let refresh_session = reject_result!(
         app.database
-            .update_refresh_session(fingerprint, refresh_token)
+            .update_refresh_session(fingerprint, refresh_token, app.jwt.refresh_lifetime())
             .await,
         401
     );
     if let Some(user) = reject_result!(app.database.get_user_by_id(refresh_session.2).await) {
+        if user.disabled {
+            reject!("error-account-disabled", 403);
+        }
         app.jwt
             .create_session_reply((refresh_session.0, refresh_session.1), user.into())
     } else {
-        reject!("Failed to find account with this id");
+        reject!("error-account-id-not-found");
     }
 }
@@ -1,4 +1,10 @@
 use anyhow::Result;
+use once_cell::sync::Lazy;
+
+/// Checked against when there is no account, so that a missing one takes as long to
+/// reject as a wrong password.
+static DUMMY_HASH: Lazy<String> =
+    Lazy::new(|| bcrypt::hash("", bcrypt::DEFAULT_COST).unwrap_or_default());
 
 pub fn hash_password(password: &str) -> Result<String> {
     Ok(bcrypt::hash(password, bcrypt::DEFAULT_COST)?)
@@ -7,3 +13,7 @@ pub fn hash_password(password: &str) -> Result<String> {
 pub fn verify_password(password: &str, hash: &str) -> Result<bool> {
     Ok(bcrypt::verify(password, hash)?)
 }
+
+pub fn verify_dummy(password: &str) {
+    let _ = bcrypt::verify(password, &DUMMY_HASH);
+}
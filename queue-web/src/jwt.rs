@@ -1,7 +1,8 @@
-use anyhow::Result;
+use anyhow::{bail, Result};
 use chrono::{Duration, TimeZone, Utc};
 use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header, Validation};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs::File;
 use std::io::Read;
 use std::path::Path;
@@ -19,49 +20,237 @@ pub struct AccessToken {
     pub role: Role,
 }
 
+#[derive(Clone, Copy, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub enum KeyAlgorithm {
+    #[default]
+    #[serde(rename = "ES256")]
+    Es256,
+    #[serde(rename = "HS256")]
+    Hs256,
+}
+
+impl From<KeyAlgorithm> for Algorithm {
+    fn from(algorithm: KeyAlgorithm) -> Self {
+        match algorithm {
+            KeyAlgorithm::Es256 => Algorithm::ES256,
+            KeyAlgorithm::Hs256 => Algorithm::HS256,
+        }
+    }
+}
+
+/// Key of an earlier rotation, tokens with its `kid` are still accepted.
+#[derive(Deserialize, Serialize)]
+pub struct VerificationKey {
+    pub id: String,
+    #[serde(default)]
+    pub algorithm: KeyAlgorithm,
+    /// Path to the EC public key, or the secret itself for HS256.
+    pub key: String,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub enum SameSite {
+    Strict,
+    Lax,
+    None,
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CookieConfig {
+    #[serde(default = "default_cookie_path")]
+    pub path: String,
+    #[serde(default)]
+    pub domain: Option<String>,
+    /// Needed when the panel is served over HTTPS from another site.
+    #[serde(default)]
+    pub secure: bool,
+    #[serde(default)]
+    pub same_site: Option<SameSite>,
+}
+
+fn default_cookie_path() -> String {
+    "/api/user/auth".to_string()
+}
+
+impl Default for CookieConfig {
+    fn default() -> Self {
+        CookieConfig {
+            path: default_cookie_path(),
+            domain: None,
+            secure: false,
+            same_site: None,
+        }
+    }
+}
+
+impl CookieConfig {
+    /// `Set-Cookie` value for the refresh token expiring at the `expires` timestamp.
+    fn header(&self, value: &str, expires: i64) -> String {
+        let expires = Utc
+            .timestamp_opt(expires, 0)
+            .unwrap()
+            .format("%a, %d %b %Y %H:%M:%S GMT");
+        let mut cookie = format!(
+            "refreshToken={}; Expires={}; Path={}; HttpOnly",
+            value, expires, self.path
+        );
+        if let Some(domain) = &self.domain {
+            cookie.push_str(&format!("; Domain={}", domain));
+        }
+        if self.secure {
+            cookie.push_str("; Secure");
+        }
+        if let Some(same_site) = self.same_site {
+            cookie.push_str(&format!("; SameSite={:?}", same_site));
+        }
+        cookie
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JwtConfig {
+    #[serde(default)]
+    pub algorithm: KeyAlgorithm,
+    /// Written as `kid` of new tokens.
+    #[serde(default)]
+    pub key_id: Option<String>,
+    /// HS256 signing secret, ES256 signs with `publicKey` and `privateKey`.
+    #[serde(default)]
+    pub secret: Option<String>,
+    #[serde(default)]
+    pub previous_keys: Vec<VerificationKey>,
+    /// Also how long a logged out session keeps working, access tokens are not revoked
+    /// with their refresh session. Disabled accounts and role changes apply at once.
+    #[serde(default = "default_access_token_minutes")]
+    pub access_token_minutes: i64,
+    #[serde(default = "default_refresh_token_days")]
+    pub refresh_token_days: i64,
+    #[serde(default)]
+    pub cookie: CookieConfig,
+}
+
+fn default_access_token_minutes() -> i64 {
+    15
+}
+
+fn default_refresh_token_days() -> i64 {
+    14
+}
+
+impl Default for JwtConfig {
+    fn default() -> Self {
+        JwtConfig {
+            algorithm: KeyAlgorithm::default(),
+            key_id: None,
+            secret: None,
+            previous_keys: Vec::new(),
+            access_token_minutes: default_access_token_minutes(),
+            refresh_token_days: default_refresh_token_days(),
+            cookie: CookieConfig::default(),
+        }
+    }
+}
+
 pub struct Jwt {
+    algorithm: Algorithm,
+    key_id: Option<String>,
     decoding_key: DecodingKey<'static>,
     encoding_key: EncodingKey,
+    previous_keys: HashMap<String, (Algorithm, DecodingKey<'static>)>,
+    access_lifetime: Duration,
+    refresh_lifetime: Duration,
+    cookie: CookieConfig,
 }
 
-impl From<AuthInfo> for AccessToken {
-    fn from(auth_info: AuthInfo) -> Self {
-        AccessToken {
-            sub: auth_info.id,
-            exp: (Utc::now() + Duration::minutes(15)).timestamp(),
-            username: auth_info.username,
-            role: auth_info.role,
-        }
-    }
+fn read_key<P: AsRef<Path>>(path: P) -> Result<Vec<u8>> {
+    let mut buffer = Vec::new();
+    File::open(path)?.read_to_end(&mut buffer)?;
+    Ok(buffer)
+}
+
+fn verification_key(algorithm: KeyAlgorithm, key: &str) -> Result<DecodingKey<'static>> {
+    Ok(match algorithm {
+        KeyAlgorithm::Es256 => DecodingKey::from_ec_pem(&read_key(key)?)?.into_static(),
+        KeyAlgorithm::Hs256 => DecodingKey::from_secret(key.as_bytes()).into_static(),
+    })
 }
 
 impl Jwt {
-    pub fn new<P: AsRef<Path>>(public_key: P, private_key: P) -> Result<Self> {
-        let mut decoding_buffer = Box::leak(Box::new(Vec::new()));
-        let mut encoding_buffer = Box::leak(Box::new(Vec::new()));
-        File::open(public_key)?.read_to_end(&mut decoding_buffer)?;
-        File::open(private_key)?.read_to_end(&mut encoding_buffer)?;
+    pub fn new<P: AsRef<Path>>(public_key: P, private_key: P, config: JwtConfig) -> Result<Self> {
+        let (decoding_key, encoding_key) = match config.algorithm {
+            KeyAlgorithm::Es256 => (
+                DecodingKey::from_ec_pem(&read_key(public_key)?)?.into_static(),
+                EncodingKey::from_ec_pem(&read_key(private_key)?)?,
+            ),
+            KeyAlgorithm::Hs256 => match config.secret.as_deref() {
+                Some(secret) if !secret.is_empty() => (
+                    DecodingKey::from_secret(secret.as_bytes()).into_static(),
+                    EncodingKey::from_secret(secret.as_bytes()),
+                ),
+                _ => bail!("HS256 needs a non-empty jwt.secret"),
+            },
+        };
+        let mut previous_keys = HashMap::new();
+        for key in config.previous_keys {
+            if config.key_id.as_ref() == Some(&key.id) {
+                bail!("Previous key {} has the id of the current key", key.id);
+            }
+            let decoding_key = verification_key(key.algorithm, &key.key)?;
+            previous_keys.insert(key.id, (key.algorithm.into(), decoding_key));
+        }
+        if config.cookie.same_site == Some(SameSite::None) && !config.cookie.secure {
+            bail!("Browsers drop SameSite=None cookies that are not Secure");
+        }
         Ok(Jwt {
-            decoding_key: DecodingKey::from_ec_pem(decoding_buffer)?,
-            encoding_key: EncodingKey::from_ec_pem(encoding_buffer)?,
+            algorithm: config.algorithm.into(),
+            key_id: config.key_id,
+            decoding_key,
+            encoding_key,
+            previous_keys,
+            access_lifetime: Duration::minutes(config.access_token_minutes),
+            refresh_lifetime: Duration::days(config.refresh_token_days),
+            cookie: config.cookie,
         })
     }
 
+    pub fn refresh_lifetime(&self) -> Duration {
+        self.refresh_lifetime
+    }
+
+    pub fn access_token(&self, auth_info: AuthInfo) -> AccessToken {
+        AccessToken {
+            sub: auth_info.id,
+            exp: (Utc::now() + self.access_lifetime).timestamp(),
+            username: auth_info.username,
+            role: auth_info.role,
+        }
+    }
+
     pub fn create_token(&self, access_token: &AccessToken) -> Result<String> {
+        let mut header = Header::new(self.algorithm);
+        header.kid = self.key_id.clone();
         Ok(jsonwebtoken::encode(
-            &Header::new(Algorithm::ES256),
+            &header,
             access_token,
             &self.encoding_key,
         )?)
     }
 
+    /// Checks the token with the key its `kid` names, the algorithm comes from that key.
     pub fn decode_token(&self, token: &str) -> Result<AccessToken> {
-        Ok(jsonwebtoken::decode(
-            token,
-            &self.decoding_key,
-            &Validation::new(Algorithm::ES256),
-        )?
-        .claims)
+        let kid = jsonwebtoken::decode_header(token)?.kid;
+        let (algorithm, key) = if kid == self.key_id {
+            (self.algorithm, &self.decoding_key)
+        } else if let Some((algorithm, key)) =
+            kid.as_ref().and_then(|kid| self.previous_keys.get(kid))
+        {
+            (*algorithm, key)
+        } else {
+            bail!("Token is signed with an unknown key");
+        };
+        Ok(jsonwebtoken::decode(token, key, &Validation::new(algorithm))?.claims)
     }
 
     pub fn create_session_reply(
@@ -69,34 +258,26 @@ impl Jwt {
         refresh_session: (Uuid, i64),
         auth_info: AuthInfo,
     ) -> Result<impl Reply, warp::Rejection> {
-        let access_token = AccessToken::from(auth_info);
+        let access_token = self.access_token(auth_info);
         let reply = warp::reply::json(&serde_json::json!({
             "accessToken": reject_result!(self.create_token(&access_token)),
             "exp": access_token.exp,
             "refreshSession": refresh_session.1,
             "role": access_token.role
         }));
-        let expires = Utc
-            .timestamp(refresh_session.1, 0)
-            .format("%a, %d %b %Y %H:%M:%S GMT")
-            .to_string();
-        //TODO add SameSite=None; Secure if want use SSL
         Ok(warp::reply::with_header(
             reply,
             "Set-Cookie",
-            format!(
-                "refreshToken={}; Expires={}; Path=/api/user/auth; HttpOnly",
-                refresh_session.0, expires,
-            ),
+            self.cookie
+                .header(&refresh_session.0.to_string(), refresh_session.1),
         ))
     }
 
     pub fn invalidate_session(&self) -> Result<impl Reply, warp::Rejection> {
-        //TODO add SameSite=None; Secure if want use SSL
         Ok(warp::reply::with_header(
             warp::reply::reply(),
-            "Set-Cookie", 
-            "refreshToken=invalid; Expires=Thu, 01 Jan 1970 00:00:00 GMT; Path=/api/user/auth; HttpOnly"
+            "Set-Cookie",
+            self.cookie.header("invalid", 0),
         ))
     }
 }
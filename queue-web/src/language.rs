@@ -0,0 +1,25 @@
+use queue_core::i18n::Language;
+use warp::http::header::ACCEPT_LANGUAGE;
+use warp::http::Request;
+
+tokio::task_local! {
+    static LANGUAGE: Language;
+}
+
+/// Language of the request that is being handled, see `main.rs`.
+pub fn current() -> Language {
+    LANGUAGE.try_with(|language| *language).unwrap_or_default()
+}
+
+pub fn of_request<B>(request: &Request<B>) -> Language {
+    request
+        .headers()
+        .get(ACCEPT_LANGUAGE)
+        .and_then(|header| header.to_str().ok())
+        .and_then(Language::from_accept_language)
+        .unwrap_or_default()
+}
+
+pub async fn scope<F: std::future::Future>(language: Language, future: F) -> F::Output {
+    LANGUAGE.scope(language, future).await
+}
@@ -1 +1,79 @@
+use anyhow::Result;
+use lettre::message::header::ContentType;
+use lettre::message::Mailbox;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
 
+#[derive(Clone, Copy, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Encryption {
+    /// Plain connection, for a local SMTP sink
+    None,
+    StartTls,
+    Tls,
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MailConfig {
+    pub host: String,
+    pub port: u16,
+    pub encryption: Encryption,
+    #[serde(default)]
+    pub username: Option<String>,
+    #[serde(default)]
+    pub password: Option<String>,
+    pub from: String,
+    /// Address of the front-end, links in the letters lead there
+    pub site_url: String,
+}
+
+pub struct Mailer {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+    site_url: String,
+}
+
+impl Mailer {
+    pub fn new(config: &MailConfig) -> Result<Self> {
+        let builder = match config.encryption {
+            Encryption::None => {
+                AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&config.host)
+            }
+            Encryption::StartTls => {
+                AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&config.host)?
+            }
+            Encryption::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(&config.host)?,
+        }
+        .port(config.port);
+        let builder = match (&config.username, &config.password) {
+            (Some(username), Some(password)) => {
+                builder.credentials(Credentials::new(username.clone(), password.clone()))
+            }
+            _ => builder,
+        };
+        Ok(Mailer {
+            transport: builder.build(),
+            from: config.from.parse()?,
+            site_url: config.site_url.trim_end_matches('/').to_string(),
+        })
+    }
+
+    /// Link to a front-end page that takes the token from the query string.
+    pub fn link(&self, page: &str, token: Uuid) -> String {
+        format!("{}/{}?token={}", self.site_url, page, token)
+    }
+
+    pub async fn send(&self, to: &str, subject: String, body: String) -> Result<()> {
+        let message = Message::builder()
+            .from(self.from.clone())
+            .to(to.parse()?)
+            .subject(subject)
+            .header(ContentType::TEXT_PLAIN)
+            .body(body)?;
+        self.transport.send(message).await?;
+        Ok(())
+    }
+}
@@ -1,35 +1,64 @@
 use anyhow::Result;
+use chrono::Utc;
+use hyper::server::conn::AddrStream;
+use hyper::service::{make_service_fn, service_fn, Service};
+use hyper::{Body, Request, Server};
 use log::info;
+use queue_core::migrate;
+use sqlx::PgPool;
+use std::convert::Infallible;
 use std::net::SocketAddrV4;
+use std::time::Duration;
 use warp::Filter;
 
-use crate::captcha::ReCaptcha;
+use crate::captcha::{CaptchaVerifier, ReCaptcha};
 use crate::config::Config;
 use crate::database::Database;
+use crate::events::Events;
+use crate::filter::ClientAddr;
 use crate::jwt::Jwt;
+use crate::mail::Mailer;
+use crate::throttle::ThrottleConfig;
 
 mod captcha;
 mod config;
 mod database;
+mod events;
 mod filter;
 mod handlers;
 mod hash;
 mod jwt;
+mod language;
 mod mail;
 mod model;
 mod reject;
+mod throttle;
+
+/// How often expired refresh sessions and account tokens are removed.
+const PURGE_INTERVAL: Duration = Duration::from_secs(60 * 60);
 
 pub struct Application {
     database: Database,
+    events: Events,
     jwt: Jwt,
-    recaptcha: ReCaptcha,
+    captcha: Box<dyn CaptchaVerifier>,
+    open_registration: bool,
+    mailer: Option<Mailer>,
+    throttle: ThrottleConfig,
 }
 impl Application {
     async fn new(config: Config) -> Result<Self> {
         Ok(Application {
             database: Database::new(config.max_connections, &config.database_url).await?,
-            jwt: Jwt::new(config.public_key, config.private_key)?,
-            recaptcha: ReCaptcha::new(config.recaptcha_token),
+            events: Events::new(),
+            jwt: Jwt::new(config.public_key, config.private_key, config.jwt)?,
+            captcha: match config.captcha {
+                Some(captcha) => captcha.verifier(),
+                None => Box::new(ReCaptcha::new(config.recaptcha_token)),
+            },
+            open_registration: config.open_registration,
+            mailer: config.mail.as_ref().map(Mailer::new).transpose()?,
+            throttle: config.login_throttle,
         })
     }
 }
@@ -41,9 +70,35 @@ async fn main() -> Result<()> {
         .init();
     info!("Read configuration...");
     let config = Config::new("config.json")?;
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    if args.first().map(String::as_str) == Some("migrate") {
+        let pool = PgPool::connect(&config.database_url).await?;
+        return migrate::command(&pool, &args[1..]).await;
+    }
     let address: SocketAddrV4 = config.address.parse()?;
 
+    let database_url = config.database_url.clone();
     let app: &'static Application = Box::leak(Box::new(Application::new(config).await?));
+    tokio::spawn(async move {
+        if let Err(error) = app.events.run(&database_url).await {
+            log::error!("Failed to listen for queue events: {}", error);
+        }
+    });
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(PURGE_INTERVAL);
+        loop {
+            interval.tick().await;
+            match app
+                .database
+                .purge_expired(Utc::now() - app.throttle.window())
+                .await
+            {
+                Ok(0) => {}
+                Ok(purged) => info!("Purged {} expired sessions and tokens", purged),
+                Err(error) => log::error!("Failed to purge expired sessions: {}", error),
+            }
+        }
+    });
     let prefix = warp::path!("api" / ..);
     let cors = warp::cors()
         .allow_any_origin()
@@ -52,9 +107,21 @@ async fn main() -> Result<()> {
         .allow_header("authorization")
         .allow_methods(vec!["GET", "POST", "DELETE", "OPTIONS"]);
     info!("IPT-Queue starting...");
-    Ok(
-        warp::serve(prefix.and(filter::routes(app).recover(reject::recover).with(cors)))
-            .run(address)
-            .await,
-    )
+    let service =
+        warp::service(prefix.and(filter::routes(app).recover(reject::recover).with(cors)));
+    // Handlers take the language of their messages from the request, see `language.rs`,
+    // `warp::service` doesn't know the peer address so it is passed in the extensions.
+    let make_service = make_service_fn(move |connection: &AddrStream| {
+        let service = service.clone();
+        let address = ClientAddr(connection.remote_addr());
+        async move {
+            Ok::<_, Infallible>(service_fn(move |mut request: Request<Body>| {
+                request.extensions_mut().insert(address);
+                let language = language::of_request(&request);
+                language::scope(language, service.clone().call(request))
+            }))
+        }
+    });
+    Server::bind(&address.into()).serve(make_service).await?;
+    Ok(())
 }
@@ -1,3 +1,4 @@
-pub mod enrollee;
+pub mod audit;
+pub mod board;
 pub mod queue;
 pub mod user;
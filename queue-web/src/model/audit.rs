@@ -0,0 +1,72 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::FromRow;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    ChangeStatus,
+    UpdateEnrollee,
+    RegisterEnrollee,
+    UpdateSchedule,
+    DeleteSchedule,
+    ChangeRole,
+    CreateUser,
+    DisableUser,
+    EnableUser,
+    DeleteUser,
+    ForceLogout,
+    ResetPassword,
+}
+
+impl Action {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Action::ChangeStatus => "change_status",
+            Action::UpdateEnrollee => "update_enrollee",
+            Action::RegisterEnrollee => "register_enrollee",
+            Action::UpdateSchedule => "update_schedule",
+            Action::DeleteSchedule => "delete_schedule",
+            Action::ChangeRole => "change_role",
+            Action::CreateUser => "create_user",
+            Action::DisableUser => "disable_user",
+            Action::EnableUser => "enable_user",
+            Action::DeleteUser => "delete_user",
+            Action::ForceLogout => "force_logout",
+            Action::ResetPassword => "reset_password",
+        }
+    }
+}
+
+#[derive(Serialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditRecord {
+    pub id: i64,
+    pub created_at: DateTime<Utc>,
+    pub actor_id: Option<i32>,
+    pub actor: String,
+    pub action: String,
+    pub enrollee: Option<i32>,
+    pub before: Option<Value>,
+    pub after: Option<Value>,
+}
+
+/// Filters of `/api/admin/audit`, dates are inclusive.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditQuery {
+    #[serde(default)]
+    pub page: u32,
+    #[serde(default = "default_per_page")]
+    pub per_page: u32,
+    pub actor: Option<String>,
+    pub action: Option<Action>,
+    pub enrollee: Option<i32>,
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+}
+
+fn default_per_page() -> u32 {
+    50
+}
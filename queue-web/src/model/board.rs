@@ -0,0 +1,44 @@
+use chrono::{NaiveDate, NaiveTime};
+use serde::Serialize;
+use sqlx::FromRow;
+
+use crate::events::{EventKind, QueueEvent};
+use queue_core::enrollee::Status;
+
+/// Row of the public board. It must never carry personal data of the enrollee,
+/// the number is the one the bot gives the enrollee to be called by.
+#[derive(Serialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct BoardEntry {
+    pub number: i32,
+    pub time: NaiveTime,
+    pub status: Status,
+    pub desk: Option<i32>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BoardEvent {
+    pub kind: EventKind,
+    pub date: NaiveDate,
+    /// Boards of this date drop the entry, it was moved to `date`.
+    pub old_date: Option<NaiveDate>,
+    #[serde(flatten)]
+    pub entry: BoardEntry,
+}
+
+impl From<&QueueEvent> for BoardEvent {
+    fn from(event: &QueueEvent) -> Self {
+        BoardEvent {
+            kind: event.kind,
+            date: event.date,
+            old_date: event.old_date,
+            entry: BoardEntry {
+                number: event.enrollee,
+                time: event.time,
+                status: event.status,
+                desk: event.desk,
+            },
+        }
+    }
+}
@@ -1,11 +1,12 @@
 use chrono::{NaiveDate, NaiveTime};
 use hyper::StatusCode;
+use serde::Serialize;
 use sqlx::FromRow;
 use std::fmt;
 use warp::http::Response;
 use warp::Reply;
 
-use crate::model::enrollee::Status;
+use queue_core::enrollee::Status;
 
 #[derive(FromRow)]
 pub struct Queue {
@@ -17,16 +18,28 @@ pub struct Queue {
     pub phone_number: String,
     pub username: String,
     pub status: Status,
+    pub desk: Option<i32>,
     pub id: i32,
 }
 
 pub struct StudentsQueue(pub Vec<Queue>);
 
+/// Direction the enrollee applied to, as abit-parser imported it.
+#[derive(Serialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct EnrolleeApplication {
+    pub direction: String,
+    pub priority: Option<i16>,
+    pub score: Option<f64>,
+    pub status: Option<String>,
+    pub original: bool,
+}
+
 impl fmt::Display for Queue {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(
             f,
-            "{},{},{},{},{},{},{},{},{}",
+            "{},{},{},{},{},{},{},{},{},{}",
             self.last_name,
             self.name,
             self.patronymic,
@@ -35,6 +48,7 @@ impl fmt::Display for Queue {
             self.phone_number,
             self.username,
             self.status,
+            self.desk.map(|desk| desk.to_string()).unwrap_or_default(),
             self.id
         )
     }
@@ -42,16 +56,16 @@ impl fmt::Display for Queue {
 
 impl fmt::Display for StudentsQueue {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        self.0.iter().fold(Ok(()), |result, lesson| {
-            result.and_then(|_| writeln!(f, "{}", lesson))
-        })
+        self.0
+            .iter()
+            .try_for_each(|lesson| writeln!(f, "{}", lesson))
     }
 }
 
 impl Reply for StudentsQueue {
     fn into_response(self) -> warp::reply::Response {
         let body = format!(
-            "last_name,name,patronymic,date,time,phone_number,username,status,id\n{}",
+            "last_name,name,patronymic,date,time,phone_number,username,status,desk,id\n{}",
             self
         )
         .into();
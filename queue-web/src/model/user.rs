@@ -1,16 +1,55 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::{FromRow, Type};
-
-use crate::jwt::AccessToken;
+use uuid::Uuid;
 
 #[derive(PartialEq, Clone, Debug, Serialize, Deserialize, Type)]
 #[sqlx(type_name = "role", rename_all = "lowercase")]
 #[serde(rename_all = "lowercase")]
 pub enum Role {
     Admin,
+    Supervisor,
+    Operator,
     User,
 }
 
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum Permission {
+    /// Read the queue, the free time and the live events
+    ViewQueue,
+    ChangeStatus,
+    /// Register enrollees and edit their bookings
+    EditQueue,
+    Export,
+    ViewAudit,
+    ManageSchedule,
+    ManageUsers,
+}
+
+impl Role {
+    pub fn permissions(&self) -> &'static [Permission] {
+        use Permission::*;
+        match self {
+            Role::Admin => &[
+                ViewQueue,
+                ChangeStatus,
+                EditQueue,
+                Export,
+                ViewAudit,
+                ManageSchedule,
+                ManageUsers,
+            ],
+            Role::Supervisor => &[ViewQueue, ChangeStatus, EditQueue, Export, ViewAudit],
+            Role::Operator => &[ViewQueue, ChangeStatus],
+            Role::User => &[],
+        }
+    }
+
+    pub fn can(&self, permission: Permission) -> bool {
+        self.permissions().contains(&permission)
+    }
+}
+
 #[derive(FromRow)]
 pub struct User {
     pub id: i32,
@@ -18,31 +57,70 @@ pub struct User {
     pub email: String,
     pub password: String,
     pub role: Role,
+    pub disabled: bool,
+    pub email_verified: bool,
 }
 
-impl Into<AuthInfo> for User {
-    fn into(self) -> AuthInfo {
-        AuthInfo {
-            id: self.id,
-            username: self.username,
-            role: self.role,
+/// Single-use tokens sent by email.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum TokenKind {
+    VerifyEmail,
+    ResetPassword,
+}
+
+impl TokenKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TokenKind::VerifyEmail => "verify",
+            TokenKind::ResetPassword => "reset",
         }
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, FromRow)]
-pub struct AuthInfo {
+/// Account as the admin panel sees it, without the password hash.
+#[derive(Serialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct UserInfo {
     pub id: i32,
     pub username: String,
+    pub email: String,
+    pub role: Role,
+    pub disabled: bool,
+    pub sessions: i64,
+}
+
+/// Signed in browser, `id` is the token family that survives refreshes.
+#[derive(Serialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct Session {
+    pub id: Uuid,
+    pub fingerprint: String,
+    pub created_at: DateTime<Utc>,
+    pub expires_in: i64,
+    pub current: bool,
+}
+
+#[derive(Deserialize)]
+pub struct NewUser {
+    pub username: String,
+    pub email: String,
+    pub password: String,
     pub role: Role,
 }
 
-impl From<AccessToken> for AuthInfo {
-    fn from(token: AccessToken) -> Self {
+impl From<User> for AuthInfo {
+    fn from(user: User) -> Self {
         AuthInfo {
-            id: token.sub,
-            username: token.username,
-            role: token.role,
+            id: user.id,
+            username: user.username,
+            role: user.role,
         }
     }
 }
+
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct AuthInfo {
+    pub id: i32,
+    pub username: String,
+    pub role: Role,
+}
@@ -1,19 +1,36 @@
+use queue_core::i18n::{self, LocalizedError};
+use queue_core::queue::BookingError;
 use serde::{Deserialize, Serialize};
 use std::convert::Infallible;
 use warp::http::StatusCode;
 use warp::reject::Reject;
 use warp::{Rejection, Reply};
 
+use crate::language;
+
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct Error {
     message: String,
     status: u16,
 }
 
+/// Errors from the message catalogue are shown in the language of the request,
+/// any other error keeps its own message.
+fn localize(error: &anyhow::Error) -> String {
+    if let Some(error) = error.downcast_ref::<LocalizedError>() {
+        error.localize(language::current())
+    } else if let Some(error) = error.downcast_ref::<BookingError>() {
+        error.localize(language::current())
+    } else {
+        format!("{}", error)
+    }
+}
+
+/// `id` is a message id from the catalogue of `queue-core`.
 impl From<&str> for Error {
-    fn from(message: &str) -> Self {
+    fn from(id: &str) -> Self {
         Error {
-            message: message.to_string(),
+            message: i18n::message(language::current(), id, None),
             status: 400,
         }
     }
@@ -22,7 +39,25 @@ impl From<&str> for Error {
 impl From<(&str, u16)> for Error {
     fn from(reject: (&str, u16)) -> Self {
         Error {
-            message: reject.0.to_string(),
+            message: i18n::message(language::current(), reject.0, None),
+            status: reject.1,
+        }
+    }
+}
+
+impl From<LocalizedError> for Error {
+    fn from(error: LocalizedError) -> Self {
+        Error {
+            message: error.localize(language::current()),
+            status: 400,
+        }
+    }
+}
+
+impl From<(LocalizedError, u16)> for Error {
+    fn from(reject: (LocalizedError, u16)) -> Self {
+        Error {
+            message: reject.0.localize(language::current()),
             status: reject.1,
         }
     }
@@ -31,7 +66,7 @@ impl From<(&str, u16)> for Error {
 impl From<(anyhow::Error, u16)> for Error {
     fn from(reject: (anyhow::Error, u16)) -> Self {
         Error {
-            message: format!("{}", reject.0),
+            message: localize(&reject.0),
             status: reject.1,
         }
     }
@@ -40,7 +75,7 @@ impl From<(anyhow::Error, u16)> for Error {
 impl From<anyhow::Error> for Error {
     fn from(error: anyhow::Error) -> Self {
         Error {
-            message: format!("{}", error),
+            message: localize(&error),
             status: 400,
         }
     }
@@ -65,12 +100,12 @@ pub async fn recover(error: Rejection) -> Result<impl Reply, Infallible> {
 #[macro_export]
 macro_rules! reject {
     ($error:expr) => {
-        return Err(warp::reject::custom(crate::reject::Error::from($error)));
+        return Err(warp::reject::custom($crate::reject::Error::from($error)))
     };
     ($error:expr, $status:expr) => {
-        return Err(warp::reject::custom(crate::reject::Error::from((
+        return Err(warp::reject::custom($crate::reject::Error::from((
             $error, $status,
-        ))));
+        ))))
     };
 }
 
@@ -0,0 +1,226 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+/// Failures after which sign in slows down and then locks.
+#[derive(Clone, Copy, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Limits {
+    pub backoff_after: i32,
+    pub lockout_after: i32,
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ThrottleConfig {
+    #[serde(default = "default_username_limits")]
+    pub username: Limits,
+    #[serde(default = "default_ip_limits")]
+    pub ip: Limits,
+    /// Wait after reaching `backoffAfter`, doubles with every next failure.
+    #[serde(default = "default_backoff_seconds")]
+    pub backoff_seconds: i64,
+    /// Also how long failures are remembered.
+    #[serde(default = "default_lockout_minutes")]
+    pub lockout_minutes: i64,
+    /// Failures after which the login form has to pass reCAPTCHA.
+    #[serde(default)]
+    pub captcha_after: Option<i32>,
+    /// Take the client address from the last `X-Forwarded-For` entry, only behind a single
+    /// proxy that appends the address it got the request from.
+    #[serde(default)]
+    pub trust_forwarded_for: bool,
+    /// Images of the self-hosted captcha one address may request within `lockoutMinutes`.
+    #[serde(default = "default_challenges_per_ip")]
+    pub challenges_per_ip: i32,
+}
+
+fn default_username_limits() -> Limits {
+    Limits {
+        backoff_after: 3,
+        lockout_after: 10,
+    }
+}
+
+fn default_ip_limits() -> Limits {
+    Limits {
+        backoff_after: 10,
+        lockout_after: 50,
+    }
+}
+
+fn default_backoff_seconds() -> i64 {
+    1
+}
+
+fn default_lockout_minutes() -> i64 {
+    15
+}
+
+fn default_challenges_per_ip() -> i32 {
+    30
+}
+
+impl Default for ThrottleConfig {
+    fn default() -> Self {
+        ThrottleConfig {
+            username: default_username_limits(),
+            ip: default_ip_limits(),
+            backoff_seconds: default_backoff_seconds(),
+            lockout_minutes: default_lockout_minutes(),
+            captcha_after: None,
+            trust_forwarded_for: false,
+            challenges_per_ip: default_challenges_per_ip(),
+        }
+    }
+}
+
+/// Failed sign in attempts under one key, a username or a client address.
+#[derive(Clone, Debug, PartialEq, FromRow)]
+pub struct Failures {
+    pub failures: i32,
+    pub last_failure: DateTime<Utc>,
+}
+
+impl ThrottleConfig {
+    pub fn window(&self) -> Duration {
+        Duration::minutes(self.lockout_minutes)
+    }
+
+    /// When the next attempt is let through, `None` if it is right away.
+    pub fn retry_at(&self, failures: &Failures, limits: Limits) -> Option<DateTime<Utc>> {
+        if failures.failures >= limits.lockout_after {
+            Some(failures.last_failure + self.window())
+        } else if failures.failures >= limits.backoff_after {
+            let exponent = (failures.failures - limits.backoff_after).min(30) as u32;
+            // Clamped before it becomes a `Duration`, which panics past i64::MAX milliseconds
+            let seconds = self
+                .backoff_seconds
+                .saturating_mul(1 << exponent)
+                .min(self.window().num_seconds());
+            Some(failures.last_failure + Duration::seconds(seconds))
+        } else {
+            None
+        }
+    }
+
+    /// Counts an attempt at `now` under every key, unless one of them has to wait until
+    /// the returned time. A turned away attempt leaves the failures as they are, so it
+    /// doesn't move the lockout. Failures older than the window are forgotten.
+    pub fn attempt(
+        &self,
+        failures: &mut [Failures],
+        limits: &[Limits],
+        now: DateTime<Utc>,
+    ) -> Result<(), DateTime<Utc>> {
+        for failures in failures.iter_mut() {
+            if failures.last_failure <= now - self.window() {
+                failures.failures = 0;
+            }
+        }
+        for (failures, limits) in failures.iter().zip(limits) {
+            match self.retry_at(failures, *limits) {
+                Some(retry_at) if retry_at > now => return Err(retry_at),
+                _ => {}
+            }
+        }
+        for failures in failures.iter_mut() {
+            failures.failures += 1;
+            failures.last_failure = now;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    const LIMITS: Limits = Limits {
+        backoff_after: 3,
+        lockout_after: 10,
+    };
+
+    fn failures(failures: i32) -> Failures {
+        Failures {
+            failures,
+            last_failure: Utc.timestamp_opt(1_700_000_000, 0).unwrap(),
+        }
+    }
+
+    fn delay(config: &ThrottleConfig, count: i32) -> Option<Duration> {
+        let failures = failures(count);
+        config
+            .retry_at(&failures, LIMITS)
+            .map(|retry_at| retry_at - failures.last_failure)
+    }
+
+    #[test]
+    fn no_delay_below_backoff() {
+        let config = ThrottleConfig::default();
+        assert_eq!(delay(&config, 0), None);
+        assert_eq!(delay(&config, 2), None);
+    }
+
+    #[test]
+    fn backoff_doubles() {
+        let config = ThrottleConfig::default();
+        assert_eq!(delay(&config, 3), Some(Duration::seconds(1)));
+        assert_eq!(delay(&config, 4), Some(Duration::seconds(2)));
+        assert_eq!(delay(&config, 6), Some(Duration::seconds(8)));
+    }
+
+    #[test]
+    fn backoff_stays_within_window() {
+        let config = ThrottleConfig {
+            backoff_seconds: i64::MAX / 2,
+            ..ThrottleConfig::default()
+        };
+        assert_eq!(delay(&config, 9), Some(config.window()));
+        let config = ThrottleConfig {
+            backoff_seconds: 600,
+            ..ThrottleConfig::default()
+        };
+        assert_eq!(delay(&config, 3), Some(Duration::minutes(10)));
+        assert_eq!(delay(&config, 4), Some(Duration::minutes(15)));
+    }
+
+    #[test]
+    fn locks_out_for_window() {
+        let config = ThrottleConfig::default();
+        assert_eq!(delay(&config, 10), Some(Duration::minutes(15)));
+        assert_eq!(delay(&config, 50), Some(Duration::minutes(15)));
+    }
+
+    #[test]
+    fn turned_away_attempt_keeps_retry_at() {
+        let config = ThrottleConfig::default();
+        let locked = failures(10);
+        let retry_at = config.retry_at(&locked, LIMITS).unwrap();
+        let mut current = [locked.clone()];
+        for minutes in [1, 5, 14] {
+            let now = locked.last_failure + Duration::minutes(minutes);
+            assert_eq!(config.attempt(&mut current, &[LIMITS], now), Err(retry_at));
+            assert_eq!(current[0], locked);
+        }
+        assert_eq!(config.attempt(&mut current, &[LIMITS], retry_at), Ok(()));
+        assert_eq!(current[0].failures, 1);
+        assert_eq!(current[0].last_failure, retry_at);
+    }
+
+    #[test]
+    fn attempt_counts_under_every_key() {
+        let config = ThrottleConfig::default();
+        let mut current = [failures(1), failures(5)];
+        let now = current[0].last_failure + Duration::seconds(1);
+        let limits = [LIMITS, config.ip];
+        assert_eq!(config.attempt(&mut current, &limits, now), Ok(()));
+        assert_eq!(current[0].failures, 2);
+        assert_eq!(current[1].failures, 6);
+        // Waiting for the username also keeps the address as it was.
+        let mut current = [failures(4), failures(5)];
+        assert!(config.attempt(&mut current, &limits, now).is_err());
+        assert_eq!(current[1], failures(5));
+    }
+}